@@ -0,0 +1,140 @@
+use crate::*;
+
+/// what happens with bytes that are invalid in the declared charset
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CharsetMode {
+    /// 400
+    Strict,
+    /// U+FFFD in place of each invalid sequence
+    Lossy
+}
+
+impl Default for CharsetMode {
+    fn default() -> Self {
+        CharsetMode::Strict
+    }
+}
+
+// 0x80..=0x9f, `None` where windows-1252 leaves the byte undefined
+const WINDOWS_1252: [Option<char>; 32] = [
+    Some('\u{20ac}'), None, Some('\u{201a}'), Some('\u{0192}'),
+    Some('\u{201e}'), Some('\u{2026}'), Some('\u{2020}'), Some('\u{2021}'),
+    Some('\u{02c6}'), Some('\u{2030}'), Some('\u{0160}'), Some('\u{2039}'),
+    Some('\u{0152}'), None, Some('\u{017d}'), None,
+    None, Some('\u{2018}'), Some('\u{2019}'), Some('\u{201c}'),
+    Some('\u{201d}'), Some('\u{2022}'), Some('\u{2013}'), Some('\u{2014}'),
+    Some('\u{02dc}'), Some('\u{2122}'), Some('\u{0161}'), Some('\u{203a}'),
+    Some('\u{0153}'), None, Some('\u{017e}'), Some('\u{0178}')];
+
+fn invalid(charset: &str, at: usize) -> Error {
+    Error::Detail(ErrorKind::BadRequest
+            , format!("invalid {} byte sequence at {}", charset, at))
+}
+
+fn decode_single_byte<F>(v: &ByteSlice, charset: &str, mode: CharsetMode, map: F) -> Result<String>
+    where F: Fn(u8) -> Option<char> {
+    let mut s = String::with_capacity(v.len());
+
+    for (i, c) in v.iter().enumerate() {
+        match map(*c) {
+            Some(ch) => s.push(ch),
+            None => {
+                if mode == CharsetMode::Strict {
+                    return Err(invalid(charset, i));
+                }
+                s.push(std::char::REPLACEMENT_CHARACTER);
+            }
+        }
+    }
+
+    Ok(s)
+}
+
+fn decode_utf16(v: &ByteSlice, big_endian: bool, mode: CharsetMode) -> Result<String> {
+    if v.len() % 2 != 0 && mode == CharsetMode::Strict {
+        return Err(invalid("utf-16", v.len() - 1));
+    }
+
+    let units = v.chunks_exact(2).map(|p| {
+        if big_endian {
+            u16::from_be_bytes([p[0], p[1]])
+        } else {
+            u16::from_le_bytes([p[0], p[1]])
+        }
+    });
+
+    let mut s = String::with_capacity(v.len() / 2);
+
+    for (i, r) in std::char::decode_utf16(units).enumerate() {
+        match r {
+            Ok(ch) => s.push(ch),
+            Err(_) => {
+                if mode == CharsetMode::Strict {
+                    return Err(invalid("utf-16", i * 2));
+                }
+                s.push(std::char::REPLACEMENT_CHARACTER);
+            }
+        }
+    }
+
+    if v.len() % 2 != 0 {
+        s.push(std::char::REPLACEMENT_CHARACTER);
+    }
+
+    Ok(s)
+}
+
+/// decodes `v` per the Content-Type charset, the BOM of `utf-8` and `utf-16`
+/// is dropped; unknown charsets are a 415
+pub fn decode(v: ByteArray, charset: &str, mode: CharsetMode) -> Result<String> {
+    let charset = charset.trim().to_ascii_lowercase();
+
+    match charset.as_str() {
+        "utf-8" | "utf8" => {
+            let v = if v.starts_with(b"\xef\xbb\xbf") { v[3..].to_vec() } else { v };
+
+            match String::from_utf8(v) {
+                Ok(s) => Ok(s),
+                Err(e) => {
+                    if mode == CharsetMode::Strict {
+                        return Err(invalid("utf-8", e.utf8_error().valid_up_to()));
+                    }
+                    Ok(String::from_utf8_lossy(e.as_bytes()).into_owned())
+                }
+            }
+        },
+        "us-ascii" | "ascii" => {
+            decode_single_byte(&v, &charset, mode, |c| {
+                if c < 0x80 { Some(c as char) } else { None }
+            })
+        },
+        "iso-8859-1" | "latin1" | "l1" | "iso_8859-1" => {
+            decode_single_byte(&v, &charset, mode, |c| Some(c as char))
+        },
+        "windows-1252" | "cp1252" => {
+            decode_single_byte(&v, &charset, mode, |c| {
+                if c >= 0x80 && c <= 0x9f {
+                    WINDOWS_1252[(c - 0x80) as usize]
+                } else {
+                    Some(c as char)
+                }
+            })
+        },
+        "utf-16be" => decode_utf16(&v, true, mode),
+        "utf-16le" => decode_utf16(&v, false, mode),
+        "utf-16" => {
+            // RFC 2781, big endian without a BOM
+            if v.starts_with(b"\xff\xfe") {
+                decode_utf16(&v[2..], false, mode)
+            } else if v.starts_with(b"\xfe\xff") {
+                decode_utf16(&v[2..], true, mode)
+            } else {
+                decode_utf16(&v, true, mode)
+            }
+        },
+        _ => {
+            Err(Error::Detail(ErrorKind::UnsupportedMediaType
+                    , format!("unsupported charset: {}", charset)))
+        }
+    }
+}
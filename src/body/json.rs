@@ -0,0 +1,52 @@
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+use crate::*;
+use crate::header::ContentType;
+use crate::body::structured::Format;
+
+pub type JsonDecoder = fn(&ByteSlice) -> Result<SendAny>;
+
+/// `application/json` and any `+json` suffix type
+pub fn is_json(content_type: &ContentType) -> bool {
+    content_type.is("application/json") || content_type.sub_type().ends_with("+json")
+}
+
+/// `BodyType::Json(json::decoder::<T>)` makes `Body::Json` carry a `T`
+pub fn decoder<T: DeserializeOwned + Send + Sync + 'static>(v: &ByteSlice) -> Result<SendAny> {
+    match serde_json::from_slice::<T>(v) {
+        Ok(t) => Ok(Box::new(t)),
+        Err(e) => {
+            Err(Error::Detail(ErrorKind::BadRequest
+                    , format!("invalid json at line {} column {}: {}", e.line(), e.column(), e)))
+        }
+    }
+}
+
+impl Body {
+    pub fn json_ref<T: 'static>(&self) -> Option<&T> {
+        match self {
+            Body::Json(v) => v.downcast_ref::<T>(),
+            _ => None
+        }
+    }
+
+    pub fn into_json<T: 'static>(self) -> Option<T> {
+        match self {
+            Body::Json(v) => {
+                match v.downcast::<T>() {
+                    Ok(t) => Some(*t),
+                    Err(_) => None
+                }
+            },
+            _ => None
+        }
+    }
+}
+
+impl ResponseContent {
+    /// 200 with the serialized value and `Content-Type: application/json`
+    pub fn json<T: Serialize + ?Sized>(value: &T) -> Result<ResponseContent> {
+        ResponseContent::encode(Format::Json, value)
+    }
+}
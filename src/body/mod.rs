@@ -0,0 +1,10 @@
+pub mod multipart;
+pub mod urlencoded;
+pub mod json;
+pub mod structured;
+pub mod charset;
+
+pub use multipart::{Multipart, MultipartLimits, Part, PartHeaders};
+pub use urlencoded::FormData;
+pub use structured::Format;
+pub use charset::CharsetMode;
@@ -0,0 +1,325 @@
+use crate::*;
+use crate::header::{self, ContentType, TypedHeader};
+use crate::stream_handler::body::BodyStream;
+use crate::stream_handler::parser::{self, RawHeaders};
+
+#[derive(Debug, Clone, Copy)]
+pub struct MultipartLimits {
+    pub max_parts: usize,
+    /// body bytes of a single part
+    pub max_part_size: usize,
+    /// header section of a single part
+    pub max_part_headers: usize
+}
+
+impl Default for MultipartLimits {
+    fn default() -> Self {
+        Self {
+            max_parts: 128,
+            max_part_size: 8 * 1024 * 1024,
+            max_part_headers: 8 * 1024
+        }
+    }
+}
+
+pub struct PartHeaders {
+    pub name: Option<String>,
+    pub filename: Option<String>,
+    pub content_type: Option<ContentType>,
+    pub headers: RawHeaders
+}
+
+impl PartHeaders {
+    fn parse(block: &ByteSlice) -> Result<Self> {
+        let mut headers: RawHeaders = Vec::new();
+
+        for line in block.split(|c| *c == b'\n') {
+            let line = match line.last() {
+                Some(b'\r') => &line[..line.len() - 1],
+                _ => line
+            };
+
+            if line.is_empty() {
+                continue;
+            }
+
+            headers.push(parser::parse_header_line(line, ParseMode::Lenient)?);
+        }
+
+        let mut part = Self {
+            name: None,
+            filename: None,
+            content_type: None,
+            headers: Vec::new()
+        };
+
+        for (name, value) in &headers {
+            if name.eq_ignore_ascii_case(b"content-disposition") {
+                let value = header::to_str("Content-Disposition", value)?;
+                let items = header::split_list(value, b';');
+                for (n, v) in header::parse_params(items.get(1..).unwrap_or(&[]))? {
+                    match n.as_str() {
+                        "name" => part.name = Some(v),
+                        "filename" => part.filename = Some(v),
+                        _ => {}
+                    }
+                }
+            } else if name.eq_ignore_ascii_case(b"content-type") {
+                part.content_type = Some(ContentType::parse(value)?);
+            }
+        }
+
+        part.headers = headers;
+
+        Ok(part)
+    }
+}
+
+enum State {
+    Delimiter,
+    Headers,
+    Body,
+    Done
+}
+
+/// `multipart/form-data` over a request body stream, one part at a time
+pub struct Multipart {
+    body: BodyStream,
+    delimiter: ByteArray,
+    buf: ByteArray,
+    state: State,
+    parts: usize,
+    part_size: usize,
+    limits: MultipartLimits
+}
+
+fn find(hay: &ByteSlice, needle: &ByteSlice) -> Option<usize> {
+    if needle.len() > hay.len() {
+        return None;
+    }
+
+    hay.windows(needle.len()).position(|w| w == needle)
+}
+
+fn bad_multipart(desc: String) -> Error {
+    Error::Detail(ErrorKind::BadRequest, desc)
+}
+
+impl Multipart {
+    /// false once the body has ended
+    async fn fill(&mut self) -> Result<bool> {
+        match self.body.next_chunk().await? {
+            Some(chunk) => {
+                self.buf.extend_from_slice(&chunk);
+                Ok(true)
+            },
+            None => Ok(false)
+        }
+    }
+
+    async fn skip_delimiter(&mut self) -> Result<()> {
+        loop {
+            if let Some(i) = find(&self.buf, &self.delimiter) {
+                let end = i + self.delimiter.len();
+
+                // "--" closes the body, otherwise transport padding and CRLF follow
+                while self.buf.len() < end + 2 {
+                    if !self.fill().await? {
+                        return Err(bad_multipart(
+                                format!("multipart body ended after a delimiter")));
+                    }
+                }
+
+                if &self.buf[end..end + 2] == b"--" {
+                    self.buf.clear();
+                    self.state = State::Done;
+                    return self.body.discard().await;
+                }
+
+                let lf = loop {
+                    if let Some(j) = self.buf[end..].iter().position(|c| *c == b'\n') {
+                        break end + j;
+                    }
+
+                    if self.buf.len() - end > self.limits.max_part_headers {
+                        return Err(bad_multipart(
+                                format!("multipart delimiter line too long")));
+                    }
+
+                    if !self.fill().await? {
+                        return Err(bad_multipart(
+                                format!("multipart body ended after a delimiter")));
+                    }
+                };
+
+                if !self.buf[end..lf].iter().all(|c| *c == b' ' || *c == b'\t' || *c == b'\r') {
+                    return Err(bad_multipart(
+                            format!("garbage after multipart delimiter")));
+                }
+
+                self.buf.drain(..lf + 1);
+                self.state = State::Headers;
+                return Ok(());
+            }
+
+            // preamble is dropped, keeping a possible partial delimiter
+            let keep = std::cmp::min(self.buf.len(), self.delimiter.len() - 1);
+            let drop_n = self.buf.len() - keep;
+            self.buf.drain(..drop_n);
+
+            if !self.fill().await? {
+                return Err(bad_multipart(
+                        format!("multipart body without closing delimiter")));
+            }
+        }
+    }
+
+    async fn read_headers(&mut self) -> Result<PartHeaders> {
+        loop {
+            // an empty header section is just the CRLF
+            let end = if self.buf.starts_with(b"\r\n") {
+                Some((0, 2))
+            } else {
+                find(&self.buf, b"\r\n\r\n").map(|i| (i, i + 4))
+            };
+
+            if let Some((i, skip)) = end {
+                let headers = PartHeaders::parse(&self.buf[..i])?;
+                self.buf.drain(..skip);
+                return Ok(headers);
+            }
+
+            if self.buf.len() > self.limits.max_part_headers {
+                return Err(Error::Detail(ErrorKind::PayloadTooLarge
+                        , format!("multipart part headers exceed limit {}", self.limits.max_part_headers)));
+            }
+
+            if !self.fill().await? {
+                return Err(bad_multipart(
+                        format!("multipart body ended in part headers")));
+            }
+        }
+    }
+
+    async fn next_part_chunk(&mut self) -> Result<Option<ByteArray>> {
+        loop {
+            match self.state {
+                State::Body => {},
+                _ => {
+                    return Ok(None);
+                }
+            }
+
+            let data = match find(&self.buf, &self.delimiter) {
+                Some(i) => {
+                    let data: ByteArray = self.buf.drain(..i).collect();
+                    self.state = State::Delimiter;
+                    data
+                },
+                None => {
+                    let safe = self.buf.len().saturating_sub(self.delimiter.len() - 1);
+                    let data: ByteArray = self.buf.drain(..safe).collect();
+
+                    if data.is_empty() && !self.fill().await? {
+                        return Err(bad_multipart(
+                                format!("multipart body ended in a part")));
+                    }
+
+                    data
+                }
+            };
+
+            if data.is_empty() {
+                continue;
+            }
+
+            self.part_size += data.len();
+            if self.part_size > self.limits.max_part_size {
+                return Err(Error::Detail(ErrorKind::PayloadTooLarge
+                        , format!("multipart part exceeds limit {}", self.limits.max_part_size)));
+            }
+
+            return Ok(Some(data));
+        }
+    }
+
+    /// unread data of the previous part is skipped
+    pub async fn next_part(&mut self) -> Result<Option<Part<'_>>> {
+        while let Some(_) = self.next_part_chunk().await? {
+        }
+
+        if let State::Delimiter = self.state {
+            self.skip_delimiter().await?;
+        }
+
+        if let State::Done = self.state {
+            return Ok(None);
+        }
+
+        self.parts += 1;
+        if self.parts > self.limits.max_parts {
+            return Err(Error::Detail(ErrorKind::PayloadTooLarge
+                    , format!("multipart parts exceed limit {}", self.limits.max_parts)));
+        }
+
+        let headers = self.read_headers().await?;
+        self.state = State::Body;
+        self.part_size = 0;
+
+        Ok(Some(Part {
+            headers: headers,
+            multipart: self
+        }))
+    }
+
+    pub fn new(body: BodyStream, content_type: &ContentType, limits: MultipartLimits) -> Result<Self> {
+        if content_type.main_type() != "multipart" {
+            return Err(Error::Detail(ErrorKind::UnsupportedMediaType
+                    , format!("not a multipart body: {}", content_type.mime())));
+        }
+
+        let boundary = match content_type.boundary() {
+            Some(b) if !b.is_empty() && b.len() <= 70 => b,
+            _ => {
+                return Err(bad_multipart(
+                        format!("multipart without a valid boundary")));
+            }
+        };
+
+        let mut delimiter = b"\r\n--".to_vec();
+        delimiter.extend_from_slice(boundary.as_bytes());
+
+        Ok(Self {
+            body: body,
+            delimiter: delimiter,
+            // the first delimiter may start the body without a CRLF in front
+            buf: b"\r\n".to_vec(),
+            state: State::Delimiter,
+            parts: 0,
+            part_size: 0,
+            limits: limits
+        })
+    }
+}
+
+pub struct Part<'a> {
+    pub headers: PartHeaders,
+    multipart: &'a mut Multipart
+}
+
+impl<'a> Part<'a> {
+    /// `None` at the end of this part
+    pub async fn next_chunk(&mut self) -> Result<Option<ByteArray>> {
+        self.multipart.next_part_chunk().await
+    }
+
+    pub async fn read_to_end(&mut self) -> Result<ByteArray> {
+        let mut buf = new_bytearray();
+
+        while let Some(chunk) = self.next_chunk().await? {
+            buf.extend_from_slice(&chunk);
+        }
+
+        Ok(buf)
+    }
+}
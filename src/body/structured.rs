@@ -0,0 +1,119 @@
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+use crate::*;
+use crate::header::ContentType;
+use crate::body::json;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    MessagePack,
+    Cbor
+}
+
+impl Format {
+    pub fn from_content_type(content_type: &ContentType) -> Option<Format> {
+        if json::is_json(content_type) {
+            return Some(Format::Json);
+        }
+
+        match content_type.mime() {
+            "application/msgpack" | "application/x-msgpack" | "application/vnd.msgpack" => {
+                Some(Format::MessagePack)
+            },
+            "application/cbor" => {
+                Some(Format::Cbor)
+            },
+            _ if content_type.sub_type().ends_with("+cbor") => {
+                Some(Format::Cbor)
+            },
+            _ => None
+        }
+    }
+
+    pub fn content_type(&self) -> ContentType {
+        match self {
+            Format::Json => ContentType::application_json(),
+            Format::MessagePack => ContentType::new("application/msgpack"),
+            Format::Cbor => ContentType::new("application/cbor")
+        }
+    }
+}
+
+pub type StructuredDecoder = fn(Format, &ByteSlice) -> Result<SendAny>;
+
+/// `BodyType::Structured(structured::decoder::<T>)` decodes a `T` from
+/// JSON, MessagePack or CBOR depending on the request Content-Type
+pub fn decoder<T: DeserializeOwned + Send + Sync + 'static>(format: Format, v: &ByteSlice) -> Result<SendAny> {
+    match format {
+        Format::Json => {
+            json::decoder::<T>(v)
+        },
+        Format::MessagePack => {
+            match rmp_serde::from_slice::<T>(v) {
+                Ok(t) => Ok(Box::new(t)),
+                Err(e) => {
+                    Err(Error::Detail(ErrorKind::BadRequest
+                            , format!("invalid msgpack: {}", e)))
+                }
+            }
+        },
+        Format::Cbor => {
+            match ciborium::de::from_reader::<T, _>(v) {
+                Ok(t) => Ok(Box::new(t)),
+                Err(ciborium::de::Error::Syntax(offset)) => {
+                    Err(Error::Detail(ErrorKind::BadRequest
+                            , format!("invalid cbor at offset {}", offset)))
+                },
+                Err(e) => {
+                    Err(Error::Detail(ErrorKind::BadRequest
+                            , format!("invalid cbor: {:?}", e)))
+                }
+            }
+        }
+    }
+}
+
+pub fn encode<T: Serialize + ?Sized>(format: Format, value: &T) -> Result<ByteArray> {
+    let r = match format {
+        Format::Json => {
+            serde_json::to_vec(value).map_err(|e| e.to_string())
+        },
+        Format::MessagePack => {
+            rmp_serde::to_vec_named(value).map_err(|e| e.to_string())
+        },
+        Format::Cbor => {
+            let mut buf = new_bytearray();
+            ciborium::ser::into_writer(value, &mut buf)
+                .map(|_| buf)
+                .map_err(|e| e.to_string())
+        }
+    };
+
+    match r {
+        Ok(v) => Ok(v),
+        Err(e) => {
+            Err(Error::Content(
+                    format!("failed to serialize {:?}: {}", format, e)))
+        }
+    }
+}
+
+impl Body {
+    /// the decoded value of `Body::Json` and `Body::Structured`
+    pub fn structured_ref<T: 'static>(&self) -> Option<&T> {
+        match self {
+            Body::Json(v) => v.downcast_ref::<T>(),
+            Body::Structured(_, v) => v.downcast_ref::<T>(),
+            _ => None
+        }
+    }
+}
+
+impl ResponseContent {
+    /// 200 with the serialized value and the matching Content-Type
+    pub fn encode<T: Serialize + ?Sized>(format: Format, value: &T) -> Result<ResponseContent> {
+        response::ResponseBuilder::new().encoded(format, value)?.build()
+    }
+}
@@ -0,0 +1,120 @@
+use crate::*;
+use crate::body::charset::{self, CharsetMode};
+
+/// ordered multi-map of decoded form fields
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FormData(Vec<(String, String)>);
+
+impl FormData {
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.0.iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn get_all<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        self.0.iter()
+            .filter(move |(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &(String, String)> {
+        self.0.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn push(&mut self, name: String, value: String) {
+        self.0.push((name, value));
+    }
+
+    pub fn new() -> Self {
+        Self(Vec::new())
+    }
+}
+
+fn hex_value(c: u8) -> Option<u8> {
+    match c {
+        b'0'..=b'9' => Some(c - b'0'),
+        b'a'..=b'f' => Some(c - b'a' + 10),
+        b'A'..=b'F' => Some(c - b'A' + 10),
+        _ => None
+    }
+}
+
+/// `%XX` and, for forms, `+` as space
+pub fn percent_decode(input: &ByteSlice, plus_as_space: bool) -> Result<ByteArray> {
+    let mut out = Vec::with_capacity(input.len());
+    let mut i = 0;
+
+    while i < input.len() {
+        match input[i] {
+            b'%' => {
+                let (h, l) = match (input.get(i + 1).and_then(|c| hex_value(*c))
+                                    , input.get(i + 2).and_then(|c| hex_value(*c))) {
+                    (Some(h), Some(l)) => (h, l),
+                    _ => {
+                        return Err(Error::Detail(ErrorKind::BadRequest
+                                , format!("invalid percent-encoding at {}", i)));
+                    }
+                };
+                out.push(h << 4 | l);
+                i += 3;
+                continue;
+            },
+            b'+' if plus_as_space => {
+                out.push(b' ');
+            },
+            c => {
+                out.push(c);
+            }
+        }
+
+        i += 1;
+    }
+
+    Ok(out)
+}
+
+/// `charset` comes from the Content-Type, the `_charset_` field is used without it;
+/// `mode` decides what bytes invalid in that charset do
+pub fn parse(body: &ByteSlice, charset: Option<&str>, mode: CharsetMode) -> Result<FormData> {
+    let mut raw: Vec<(ByteArray, ByteArray)> = Vec::new();
+
+    for pair in body.split(|c| *c == b'&') {
+        if pair.is_empty() {
+            continue;
+        }
+
+        let (name, value) = match pair.iter().position(|c| *c == b'=') {
+            Some(i) => (&pair[..i], &pair[i + 1..]),
+            None => (pair, &pair[0..0])
+        };
+
+        raw.push((percent_decode(name, true)?, percent_decode(value, true)?));
+    }
+
+    let charset = match charset {
+        Some(c) => c.to_ascii_lowercase(),
+        None => {
+            match raw.iter().find(|(n, _)| n == b"_charset_") {
+                Some((_, v)) => String::from_utf8_lossy(v).to_ascii_lowercase(),
+                None => "utf-8".to_string()
+            }
+        }
+    };
+
+    let mut form = FormData::new();
+    for (name, value) in raw {
+        form.push(charset::decode(name, &charset, mode)?
+                  , charset::decode(value, &charset, mode)?);
+    }
+
+    Ok(form)
+}
@@ -0,0 +1,154 @@
+use crate::*;
+use crate::header::*;
+
+pub const MAX_QUALITY: u16 = 1000;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct QualityItem {
+    pub value: String,
+    pub params: Vec<(String, String)>,
+    /// weight scaled by 1000, `q=0.5` is 500
+    pub quality: u16
+}
+
+impl QualityItem {
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params.iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn new(value: &str, quality: u16) -> Self {
+        Self {
+            value: value.to_string(),
+            params: Vec::new(),
+            quality: std::cmp::min(quality, MAX_QUALITY)
+        }
+    }
+
+    fn write(&self, buf: &mut ByteArray) {
+        buf.extend_from_slice(self.value.as_bytes());
+        write_params(&self.params, buf);
+
+        if self.quality < MAX_QUALITY {
+            buf.extend_from_slice(b";q=0.");
+            let digits = format!("{:03}", self.quality);
+            buf.extend_from_slice(digits.trim_end_matches('0').as_bytes());
+            if self.quality == 0 {
+                buf.push(b'0');
+            }
+        }
+    }
+}
+
+fn parse_quality(value: &str) -> Result<u16> {
+    let err = || invalid(format!("invalid q-value: {}", value));
+
+    let mut parts = value.splitn(2, '.');
+    let int = parts.next().unwrap_or("");
+    let frac = parts.next().unwrap_or("");
+
+    if frac.len() > 3 || !frac.bytes().all(|c| c.is_ascii_digit()) {
+        return Err(err());
+    }
+
+    let mut q: u16 = match int {
+        "0" => 0,
+        "1" => MAX_QUALITY,
+        _ => {
+            return Err(err());
+        }
+    };
+
+    let mut scale = 100;
+    for c in frac.bytes() {
+        q += (c - b'0') as u16 * scale;
+        scale /= 10;
+    }
+
+    if q > MAX_QUALITY {
+        return Err(err());
+    }
+
+    Ok(q)
+}
+
+/// parses any `Accept`-style list, `Accept-Encoding`, `Accept-Language` and `Accept-Charset` included
+pub fn parse_quality_list(value: &str) -> Result<Vec<QualityItem>> {
+    let mut list = Vec::new();
+
+    for item in split_list(value, b',') {
+        let parts = split_list(item, b';');
+        let mut params = parse_params(&parts[1..])?;
+
+        let quality = match params.iter().position(|(n, _)| n == "q") {
+            Some(i) => {
+                let q = parse_quality(&params[i].1)?;
+                params.truncate(i);
+                q
+            },
+            None => MAX_QUALITY
+        };
+
+        list.push(QualityItem {
+            value: parts[0].to_string(),
+            params: params,
+            quality: quality
+        });
+    }
+
+    Ok(list)
+}
+
+pub fn write_quality_list(list: &[QualityItem], buf: &mut ByteArray) {
+    for (i, item) in list.iter().enumerate() {
+        if i > 0 {
+            buf.extend_from_slice(b", ");
+        }
+        item.write(buf);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Accept(pub Vec<QualityItem>);
+
+impl Accept {
+    pub fn items(&self) -> &[QualityItem] {
+        &self.0
+    }
+
+    /// stable sort, highest quality first
+    pub fn sorted(&self) -> Vec<&QualityItem> {
+        let mut items: Vec<&QualityItem> = self.0.iter().collect();
+        items.sort_by(|a, b| b.quality.cmp(&a.quality));
+        items
+    }
+}
+
+impl TypedHeader for Accept {
+    fn name() -> &'static str {
+        "Accept"
+    }
+
+    fn parse(value: &ByteSlice) -> Result<Self> {
+        let value = to_str(Self::name(), value)?;
+
+        let list = parse_quality_list(value)?;
+        for item in &list {
+            let mut parts = item.value.splitn(2, '/');
+            match (parts.next(), parts.next()) {
+                (Some(t), Some(s)) if is_token(t) && is_token(s) => {},
+                _ => {
+                    return Err(invalid(
+                            format!("Accept invalid media range: {}", item.value)));
+                }
+            }
+        }
+
+        Ok(Accept(list))
+    }
+
+    fn write(&self, buf: &mut ByteArray) {
+        write_quality_list(&self.0, buf);
+    }
+}
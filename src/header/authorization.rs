@@ -0,0 +1,158 @@
+use crate::*;
+use crate::header::*;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Authorization {
+    Basic {
+        username: String,
+        password: String
+    },
+    Bearer(String),
+    Other {
+        scheme: String,
+        credentials: String
+    }
+}
+
+impl TypedHeader for Authorization {
+    fn name() -> &'static str {
+        "Authorization"
+    }
+
+    fn parse(value: &ByteSlice) -> Result<Self> {
+        let value = to_str(Self::name(), value)?;
+
+        let (scheme, credentials) = match value.find(' ') {
+            Some(i) => (&value[..i], value[i + 1..].trim()),
+            None => (value, "")
+        };
+
+        if !is_token(scheme) {
+            return Err(invalid(
+                    format!("Authorization invalid scheme: {}", scheme)));
+        }
+
+        if scheme.eq_ignore_ascii_case("basic") {
+            let decoded = match base64_decode(credentials.as_bytes()) {
+                Some(d) => d,
+                None => {
+                    return Err(invalid(
+                            format!("Authorization basic credentials are not base64")));
+                }
+            };
+
+            let decoded = match String::from_utf8(decoded) {
+                Ok(s) => s,
+                Err(_) => {
+                    return Err(invalid(
+                            format!("Authorization basic credentials are not utf-8")));
+                }
+            };
+
+            return match decoded.find(':') {
+                Some(i) => {
+                    Ok(Authorization::Basic {
+                        username: decoded[..i].to_string(),
+                        password: decoded[i + 1..].to_string()
+                    })
+                },
+                None => {
+                    Err(invalid(
+                            format!("Authorization basic credentials without ':'")))
+                }
+            };
+        }
+
+        if scheme.eq_ignore_ascii_case("bearer") {
+            if credentials.is_empty() {
+                return Err(invalid(
+                        format!("Authorization bearer token is empty")));
+            }
+
+            return Ok(Authorization::Bearer(credentials.to_string()));
+        }
+
+        Ok(Authorization::Other {
+            scheme: scheme.to_string(),
+            credentials: credentials.to_string()
+        })
+    }
+
+    fn write(&self, buf: &mut ByteArray) {
+        match self {
+            Authorization::Basic { username, password } => {
+                buf.extend_from_slice(b"Basic ");
+                base64_encode(format!("{}:{}", username, password).as_bytes(), buf);
+            },
+            Authorization::Bearer(token) => {
+                buf.extend_from_slice(b"Bearer ");
+                buf.extend_from_slice(token.as_bytes());
+            },
+            Authorization::Other { scheme, credentials } => {
+                buf.extend_from_slice(scheme.as_bytes());
+                if !credentials.is_empty() {
+                    buf.push(b' ');
+                    buf.extend_from_slice(credentials.as_bytes());
+                }
+            }
+        }
+    }
+}
+
+/////////////////////////////
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64_value(c: u8) -> Option<u8> {
+    match c {
+        b'A'..=b'Z' => Some(c - b'A'),
+        b'a'..=b'z' => Some(c - b'a' + 26),
+        b'0'..=b'9' => Some(c - b'0' + 52),
+        b'+' => Some(62),
+        b'/' => Some(63),
+        _ => None
+    }
+}
+
+pub(crate) fn base64_decode(input: &ByteSlice) -> Option<ByteArray> {
+    let input = match input.iter().position(|c| *c == b'=') {
+        Some(i) => {
+            if input[i..].len() > 2 || input[i..].iter().any(|c| *c != b'=') {
+                return None;
+            }
+            &input[..i]
+        },
+        None => input
+    };
+
+    if input.len() % 4 == 1 {
+        return None;
+    }
+
+    let mut out = Vec::with_capacity(input.len() * 3 / 4);
+    let mut acc: u32 = 0;
+    let mut bits = 0;
+
+    for c in input {
+        acc = (acc << 6) | base64_value(*c)? as u32;
+        bits += 6;
+
+        if bits >= 8 {
+            bits -= 8;
+            out.push((acc >> bits) as u8);
+        }
+    }
+
+    Some(out)
+}
+
+pub(crate) fn base64_encode(input: &ByteSlice, buf: &mut ByteArray) {
+    for chunk in input.chunks(3) {
+        let b = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let n = ((b[0] as u32) << 16) | ((b[1] as u32) << 8) | b[2] as u32;
+
+        buf.push(BASE64[(n >> 18) as usize & 63]);
+        buf.push(BASE64[(n >> 12) as usize & 63]);
+        buf.push(if chunk.len() > 1 { BASE64[(n >> 6) as usize & 63] } else { b'=' });
+        buf.push(if chunk.len() > 2 { BASE64[n as usize & 63] } else { b'=' });
+    }
+}
@@ -0,0 +1,152 @@
+use crate::*;
+use crate::header::*;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CacheDirective {
+    NoCache,
+    NoStore,
+    NoTransform,
+    OnlyIfCached,
+    MustRevalidate,
+    ProxyRevalidate,
+    Public,
+    Private,
+    Immutable,
+    MaxAge(u64),
+    SMaxAge(u64),
+    MaxStale(Option<u64>),
+    MinFresh(u64),
+    Extension(String, Option<String>)
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct CacheControl(pub Vec<CacheDirective>);
+
+impl CacheControl {
+    pub fn directives(&self) -> &[CacheDirective] {
+        &self.0
+    }
+
+    pub fn has(&self, d: &CacheDirective) -> bool {
+        self.0.contains(d)
+    }
+
+    pub fn max_age(&self) -> Option<u64> {
+        self.0.iter().find_map(|d| match d {
+            CacheDirective::MaxAge(v) => Some(*v),
+            _ => None
+        })
+    }
+
+    pub fn with(mut self, d: CacheDirective) -> Self {
+        self.0.push(d);
+        self
+    }
+
+    pub fn new() -> Self {
+        Self(Vec::new())
+    }
+}
+
+fn parse_seconds(name: &str, value: &Option<String>) -> Result<u64> {
+    match value {
+        Some(v) => {
+            match v.parse::<u64>() {
+                Ok(n) => Ok(n),
+                Err(_) => {
+                    Err(invalid(
+                            format!("Cache-Control {} invalid seconds: {}", name, v)))
+                }
+            }
+        },
+        None => {
+            Err(invalid(
+                    format!("Cache-Control {} without value", name)))
+        }
+    }
+}
+
+impl TypedHeader for CacheControl {
+    fn name() -> &'static str {
+        "Cache-Control"
+    }
+
+    fn parse(value: &ByteSlice) -> Result<Self> {
+        let value = to_str(Self::name(), value)?;
+
+        let mut directives = Vec::new();
+
+        for item in split_list(value, b',') {
+            let (name, arg) = match item.find('=') {
+                Some(i) => (item[..i].trim(), Some(unquote(&item[i + 1..])?)),
+                None => (item, None)
+            };
+
+            if !is_token(name) {
+                return Err(invalid(
+                        format!("Cache-Control invalid directive: {}", name)));
+            }
+
+            let d = match name.to_ascii_lowercase().as_str() {
+                "no-cache" => CacheDirective::NoCache,
+                "no-store" => CacheDirective::NoStore,
+                "no-transform" => CacheDirective::NoTransform,
+                "only-if-cached" => CacheDirective::OnlyIfCached,
+                "must-revalidate" => CacheDirective::MustRevalidate,
+                "proxy-revalidate" => CacheDirective::ProxyRevalidate,
+                "public" => CacheDirective::Public,
+                "private" => CacheDirective::Private,
+                "immutable" => CacheDirective::Immutable,
+                "max-age" => CacheDirective::MaxAge(parse_seconds(name, &arg)?),
+                "s-maxage" => CacheDirective::SMaxAge(parse_seconds(name, &arg)?),
+                "min-fresh" => CacheDirective::MinFresh(parse_seconds(name, &arg)?),
+                "max-stale" => {
+                    match arg {
+                        Some(_) => CacheDirective::MaxStale(Some(parse_seconds(name, &arg)?)),
+                        None => CacheDirective::MaxStale(None)
+                    }
+                },
+                _ => CacheDirective::Extension(name.to_ascii_lowercase(), arg)
+            };
+
+            directives.push(d);
+        }
+
+        Ok(CacheControl(directives))
+    }
+
+    fn write(&self, buf: &mut ByteArray) {
+        for (i, d) in self.0.iter().enumerate() {
+            if i > 0 {
+                buf.extend_from_slice(b", ");
+            }
+
+            let s = match d {
+                CacheDirective::NoCache => "no-cache".to_string(),
+                CacheDirective::NoStore => "no-store".to_string(),
+                CacheDirective::NoTransform => "no-transform".to_string(),
+                CacheDirective::OnlyIfCached => "only-if-cached".to_string(),
+                CacheDirective::MustRevalidate => "must-revalidate".to_string(),
+                CacheDirective::ProxyRevalidate => "proxy-revalidate".to_string(),
+                CacheDirective::Public => "public".to_string(),
+                CacheDirective::Private => "private".to_string(),
+                CacheDirective::Immutable => "immutable".to_string(),
+                CacheDirective::MaxAge(v) => format!("max-age={}", v),
+                CacheDirective::SMaxAge(v) => format!("s-maxage={}", v),
+                CacheDirective::MinFresh(v) => format!("min-fresh={}", v),
+                CacheDirective::MaxStale(Some(v)) => format!("max-stale={}", v),
+                CacheDirective::MaxStale(None) => "max-stale".to_string(),
+                CacheDirective::Extension(name, arg) => {
+                    buf.extend_from_slice(name.as_bytes());
+                    if let Some(arg) = arg {
+                        buf.push(b'=');
+                        write_maybe_quoted(arg, buf);
+                    }
+                    continue;
+                }
+            };
+
+            buf.extend_from_slice(s.as_bytes());
+        }
+    }
+}
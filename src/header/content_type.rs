@@ -0,0 +1,119 @@
+use crate::*;
+use crate::header::*;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContentType {
+    mime: String,
+    params: Vec<(String, String)>
+}
+
+impl ContentType {
+    /// `type/subtype`, lowercased
+    pub fn mime(&self) -> &str {
+        &self.mime
+    }
+
+    pub fn main_type(&self) -> &str {
+        match self.mime.find('/') {
+            Some(i) => &self.mime[..i],
+            None => &self.mime
+        }
+    }
+
+    pub fn sub_type(&self) -> &str {
+        match self.mime.find('/') {
+            Some(i) => &self.mime[i + 1..],
+            None => ""
+        }
+    }
+
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params.iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn params(&self) -> &[(String, String)] {
+        &self.params
+    }
+
+    pub fn charset(&self) -> Option<&str> {
+        self.param("charset")
+    }
+
+    pub fn boundary(&self) -> Option<&str> {
+        self.param("boundary")
+    }
+
+    pub fn with_param(mut self, name: &str, value: &str) -> Self {
+        let name = name.to_ascii_lowercase();
+        self.params.retain(|(n, _)| *n != name);
+        self.params.push((name, value.to_string()));
+        self
+    }
+
+    pub fn is(&self, mime: &str) -> bool {
+        self.mime.eq_ignore_ascii_case(mime)
+    }
+
+    pub fn new(mime: &str) -> Self {
+        Self {
+            mime: mime.to_ascii_lowercase(),
+            params: Vec::new()
+        }
+    }
+
+    pub fn text_plain() -> Self {
+        Self::new("text/plain").with_param("charset", "utf-8")
+    }
+
+    pub fn text_html() -> Self {
+        Self::new("text/html").with_param("charset", "utf-8")
+    }
+
+    pub fn application_json() -> Self {
+        Self::new("application/json")
+    }
+
+    pub fn octet_stream() -> Self {
+        Self::new("application/octet-stream")
+    }
+}
+
+impl TypedHeader for ContentType {
+    fn name() -> &'static str {
+        "Content-Type"
+    }
+
+    fn parse(value: &ByteSlice) -> Result<Self> {
+        let value = to_str(Self::name(), value)?;
+
+        let items = split_list(value, b';');
+        let mime = match items.first() {
+            Some(m) => *m,
+            None => {
+                return Err(invalid(
+                        format!("Content-Type is empty")));
+            }
+        };
+
+        let mut parts = mime.splitn(2, '/');
+        let (t, s) = match (parts.next(), parts.next()) {
+            (Some(t), Some(s)) if is_token(t) && is_token(s) => (t, s),
+            _ => {
+                return Err(invalid(
+                        format!("Content-Type invalid media type: {}", mime)));
+            }
+        };
+
+        Ok(Self {
+            mime: format!("{}/{}", t, s).to_ascii_lowercase(),
+            params: parse_params(&items[1..])?
+        })
+    }
+
+    fn write(&self, buf: &mut ByteArray) {
+        buf.extend_from_slice(self.mime.as_bytes());
+        write_params(&self.params, buf);
+    }
+}
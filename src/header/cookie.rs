@@ -0,0 +1,79 @@
+use crate::*;
+use crate::header::*;
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Cookie(pub Vec<(String, String)>);
+
+impl Cookie {
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.0.iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &(String, String)> {
+        self.0.iter()
+    }
+
+    pub fn with(mut self, name: &str, value: &str) -> Self {
+        self.0.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn new() -> Self {
+        Self(Vec::new())
+    }
+}
+
+impl TypedHeader for Cookie {
+    fn name() -> &'static str {
+        "Cookie"
+    }
+
+    fn parse(value: &ByteSlice) -> Result<Self> {
+        let value = to_str(Self::name(), value)?;
+
+        let mut pairs = Vec::new();
+
+        for item in value.split(';') {
+            let item = item.trim();
+            if item.is_empty() {
+                continue;
+            }
+
+            let (name, v) = match item.find('=') {
+                Some(i) => (item[..i].trim(), item[i + 1..].trim()),
+                None => {
+                    return Err(invalid(
+                            format!("Cookie pair without '=': {}", item)));
+                }
+            };
+
+            if !is_token(name) {
+                return Err(invalid(
+                        format!("Cookie invalid name: {}", name)));
+            }
+
+            let v = if v.len() >= 2 && v.starts_with('"') && v.ends_with('"') {
+                &v[1..v.len() - 1]
+            } else {
+                v
+            };
+
+            pairs.push((name.to_string(), v.to_string()));
+        }
+
+        Ok(Cookie(pairs))
+    }
+
+    fn write(&self, buf: &mut ByteArray) {
+        for (i, (name, value)) in self.0.iter().enumerate() {
+            if i > 0 {
+                buf.extend_from_slice(b"; ");
+            }
+            buf.extend_from_slice(name.as_bytes());
+            buf.push(b'=');
+            buf.extend_from_slice(value.as_bytes());
+        }
+    }
+}
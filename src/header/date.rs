@@ -0,0 +1,214 @@
+use std::time::{SystemTime, UNIX_EPOCH, Duration};
+
+use crate::*;
+use crate::header::*;
+
+const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun",
+    "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+
+/// second precision, as all http dates are
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct HttpDate(u64);
+
+impl HttpDate {
+    pub fn now() -> Self {
+        Self::from(SystemTime::now())
+    }
+
+    pub fn secs(&self) -> u64 {
+        self.0
+    }
+
+    pub fn from_secs(secs: u64) -> Self {
+        HttpDate(secs)
+    }
+
+    pub fn to_system_time(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(self.0)
+    }
+
+    /// IMF-fixdate, `Sun, 06 Nov 1994 08:49:37 GMT`
+    pub fn write(&self, buf: &mut ByteArray) {
+        let days = (self.0 / 86400) as i64;
+        let secs = self.0 % 86400;
+        let (year, month, day) = civil_from_days(days);
+        // 1970-01-01 was a Thursday
+        let weekday = ((days + 3) % 7) as usize;
+
+        let s = format!("{}, {:02} {} {:04} {:02}:{:02}:{:02} GMT"
+                        , WEEKDAYS[weekday], day, MONTHS[month as usize - 1], year
+                        , secs / 3600, secs / 60 % 60, secs % 60);
+
+        buf.extend_from_slice(s.as_bytes());
+    }
+
+    /// accepts IMF-fixdate and the obsolete RFC 850 and asctime forms
+    pub fn parse_str(s: &str) -> Result<Self> {
+        let err = || invalid(format!("invalid http date: {}", s));
+
+        let s = s.trim();
+        let parts: Vec<&str> = s.split(|c| c == ' ' || c == ',')
+            .filter(|p| !p.is_empty()).collect();
+
+        let (day, month, year, time) = match parts.len() {
+            // Sun, 06 Nov 1994 08:49:37 GMT
+            6 if parts[5] == "GMT" => {
+                (parts[1], parts[2], parts[3].to_string(), parts[4])
+            },
+            // Sunday, 06-Nov-94 08:49:37 GMT
+            4 if parts[3] == "GMT" => {
+                let dmy: Vec<&str> = parts[1].split('-').collect();
+                if dmy.len() != 3 || dmy[2].len() != 2 {
+                    return Err(err());
+                }
+                let yy = dmy[2].parse::<u64>().map_err(|_| err())?;
+                // two digit years more than 50 years in the future are in the past
+                let year = if yy < 70 { 2000 + yy } else { 1900 + yy };
+                (dmy[0], dmy[1], year.to_string(), parts[2])
+            },
+            // Sun Nov  6 08:49:37 1994
+            5 => {
+                (parts[2], parts[1], parts[4].to_string(), parts[3])
+            },
+            _ => {
+                return Err(err());
+            }
+        };
+
+        let day = day.parse::<u32>().map_err(|_| err())?;
+        let month = match MONTHS.iter().position(|m| *m == month) {
+            Some(i) => i as u32 + 1,
+            None => {
+                return Err(err());
+            }
+        };
+        let year = year.parse::<i64>().map_err(|_| err())?;
+
+        let hms: Vec<u64> = match time.split(':').map(|v| v.parse::<u64>()).collect() {
+            Ok(v) => v,
+            Err(_) => {
+                return Err(err());
+            }
+        };
+
+        // four digit years keep the arithmetic below far from overflow
+        if hms.len() != 3 || hms[0] > 23 || hms[1] > 59 || hms[2] > 60
+            || day == 0 || day > 31 || !(1970..=9999).contains(&year) {
+            return Err(err());
+        }
+
+        let days = days_from_civil(year, month, day) as u64;
+        let secs = days.checked_mul(86400)
+            .and_then(|s| s.checked_add(hms[0] * 3600 + hms[1] * 60 + hms[2]))
+            .ok_or_else(err)?;
+
+        Ok(HttpDate(secs))
+    }
+}
+
+impl From<SystemTime> for HttpDate {
+    fn from(t: SystemTime) -> Self {
+        match t.duration_since(UNIX_EPOCH) {
+            Ok(d) => HttpDate(d.as_secs()),
+            Err(_) => HttpDate(0)
+        }
+    }
+}
+
+// http://howardhinnant.github.io/date_algorithms.html
+fn days_from_civil(y: i64, m: u32, d: u32) -> i64 {
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = if y >= 0 { y } else { y - 399 } / 400;
+    let yoe = y - era * 400;
+    let mp = (m as i64 + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + d as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+fn civil_from_days(z: i64) -> (i64, u32, u32) {
+    let z = z + 719468;
+    let era = if z >= 0 { z } else { z - 146096 } / 146097;
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let y = yoe + era * 400;
+    (if m <= 2 { y + 1 } else { y }, m, d)
+}
+
+/////////////////////////////
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Date(pub HttpDate);
+
+impl TypedHeader for Date {
+    fn name() -> &'static str {
+        "Date"
+    }
+
+    fn parse(value: &ByteSlice) -> Result<Self> {
+        Ok(Date(HttpDate::parse_str(to_str(Self::name(), value)?)?))
+    }
+
+    fn write(&self, buf: &mut ByteArray) {
+        self.0.write(buf);
+    }
+}
+
+/////////////////////////////
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LastModified(pub HttpDate);
+
+impl TypedHeader for LastModified {
+    fn name() -> &'static str {
+        "Last-Modified"
+    }
+
+    fn parse(value: &ByteSlice) -> Result<Self> {
+        Ok(LastModified(HttpDate::parse_str(to_str(Self::name(), value)?)?))
+    }
+
+    fn write(&self, buf: &mut ByteArray) {
+        self.0.write(buf);
+    }
+}
+
+/////////////////////////////
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IfModifiedSince(pub HttpDate);
+
+impl TypedHeader for IfModifiedSince {
+    fn name() -> &'static str {
+        "If-Modified-Since"
+    }
+
+    fn parse(value: &ByteSlice) -> Result<Self> {
+        Ok(IfModifiedSince(HttpDate::parse_str(to_str(Self::name(), value)?)?))
+    }
+
+    fn write(&self, buf: &mut ByteArray) {
+        self.0.write(buf);
+    }
+}
+
+/////////////////////////////
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IfUnmodifiedSince(pub HttpDate);
+
+impl TypedHeader for IfUnmodifiedSince {
+    fn name() -> &'static str {
+        "If-Unmodified-Since"
+    }
+
+    fn parse(value: &ByteSlice) -> Result<Self> {
+        Ok(IfUnmodifiedSince(HttpDate::parse_str(to_str(Self::name(), value)?)?))
+    }
+
+    fn write(&self, buf: &mut ByteArray) {
+        self.0.write(buf);
+    }
+}
@@ -0,0 +1,191 @@
+use crate::*;
+use crate::header::*;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct EntityTag {
+    pub weak: bool,
+    /// opaque tag without the quotes
+    pub tag: String
+}
+
+impl EntityTag {
+    pub fn strong(tag: &str) -> Self {
+        Self {
+            weak: false,
+            tag: tag.to_string()
+        }
+    }
+
+    pub fn weak(tag: &str) -> Self {
+        Self {
+            weak: true,
+            tag: tag.to_string()
+        }
+    }
+
+    pub fn strong_eq(&self, other: &EntityTag) -> bool {
+        !self.weak && !other.weak && self.tag == other.tag
+    }
+
+    pub fn weak_eq(&self, other: &EntityTag) -> bool {
+        self.tag == other.tag
+    }
+
+    pub fn parse_one(s: &str) -> Result<Self> {
+        let s = s.trim();
+        let (weak, opaque) = if s.starts_with("W/") {
+            (true, &s[2..])
+        } else {
+            (false, s)
+        };
+
+        if opaque.len() < 2 || !opaque.starts_with('"') || !opaque.ends_with('"') {
+            return Err(invalid(
+                    format!("invalid entity-tag: {}", s)));
+        }
+
+        let tag = &opaque[1..opaque.len() - 1];
+        if tag.bytes().any(|c| c == b'"' || c < 0x21 || c == 0x7f) {
+            return Err(invalid(
+                    format!("invalid entity-tag: {}", s)));
+        }
+
+        Ok(Self {
+            weak: weak,
+            tag: tag.to_string()
+        })
+    }
+
+    pub fn write(&self, buf: &mut ByteArray) {
+        if self.weak {
+            buf.extend_from_slice(b"W/");
+        }
+        buf.push(b'"');
+        buf.extend_from_slice(self.tag.as_bytes());
+        buf.push(b'"');
+    }
+}
+
+impl TypedHeader for EntityTag {
+    fn name() -> &'static str {
+        "ETag"
+    }
+
+    fn parse(value: &ByteSlice) -> Result<Self> {
+        EntityTag::parse_one(to_str(Self::name(), value)?)
+    }
+
+    fn write(&self, buf: &mut ByteArray) {
+        EntityTag::write(self, buf);
+    }
+}
+
+/////////////////////////////
+#[derive(Debug, Clone, PartialEq)]
+pub enum IfNoneMatch {
+    Any,
+    Tags(Vec<EntityTag>)
+}
+
+impl IfNoneMatch {
+    /// weak comparison, as required for `If-None-Match`
+    pub fn matches(&self, etag: &EntityTag) -> bool {
+        match self {
+            IfNoneMatch::Any => true,
+            IfNoneMatch::Tags(tags) => tags.iter().any(|t| t.weak_eq(etag))
+        }
+    }
+}
+
+pub(crate) fn parse_tag_list(name: &str, value: &ByteSlice) -> Result<Option<Vec<EntityTag>>> {
+    let value = to_str(name, value)?;
+
+    if value == "*" {
+        return Ok(None);
+    }
+
+    let mut tags = Vec::new();
+    for item in split_list(value, b',') {
+        tags.push(EntityTag::parse_one(item)?);
+    }
+
+    if tags.is_empty() {
+        return Err(invalid(
+                format!("{} is empty", name)));
+    }
+
+    Ok(Some(tags))
+}
+
+pub(crate) fn write_tag_list(tags: &Option<Vec<EntityTag>>, buf: &mut ByteArray) {
+    match tags {
+        None => {
+            buf.push(b'*');
+        },
+        Some(tags) => {
+            for (i, t) in tags.iter().enumerate() {
+                if i > 0 {
+                    buf.extend_from_slice(b", ");
+                }
+                t.write(buf);
+            }
+        }
+    }
+}
+
+impl TypedHeader for IfNoneMatch {
+    fn name() -> &'static str {
+        "If-None-Match"
+    }
+
+    fn parse(value: &ByteSlice) -> Result<Self> {
+        match parse_tag_list(Self::name(), value)? {
+            None => Ok(IfNoneMatch::Any),
+            Some(tags) => Ok(IfNoneMatch::Tags(tags))
+        }
+    }
+
+    fn write(&self, buf: &mut ByteArray) {
+        match self {
+            IfNoneMatch::Any => write_tag_list(&None, buf),
+            IfNoneMatch::Tags(tags) => write_tag_list(&Some(tags.clone()), buf)
+        }
+    }
+}
+
+/////////////////////////////
+#[derive(Debug, Clone, PartialEq)]
+pub enum IfMatch {
+    Any,
+    Tags(Vec<EntityTag>)
+}
+
+impl IfMatch {
+    /// strong comparison, as required for `If-Match`
+    pub fn matches(&self, etag: &EntityTag) -> bool {
+        match self {
+            IfMatch::Any => true,
+            IfMatch::Tags(tags) => tags.iter().any(|t| t.strong_eq(etag))
+        }
+    }
+}
+
+impl TypedHeader for IfMatch {
+    fn name() -> &'static str {
+        "If-Match"
+    }
+
+    fn parse(value: &ByteSlice) -> Result<Self> {
+        match parse_tag_list(Self::name(), value)? {
+            None => Ok(IfMatch::Any),
+            Some(tags) => Ok(IfMatch::Tags(tags))
+        }
+    }
+
+    fn write(&self, buf: &mut ByteArray) {
+        match self {
+            IfMatch::Any => write_tag_list(&None, buf),
+            IfMatch::Tags(tags) => write_tag_list(&Some(tags.clone()), buf)
+        }
+    }
+}
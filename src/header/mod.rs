@@ -0,0 +1,230 @@
+use crate::*;
+
+pub mod content_type;
+pub mod accept;
+pub mod authorization;
+pub mod cache_control;
+pub mod range;
+pub mod entity_tag;
+pub mod cookie;
+pub mod date;
+pub mod negotiate;
+
+pub use content_type::ContentType;
+pub use accept::{Accept, QualityItem};
+pub use authorization::Authorization;
+pub use cache_control::{CacheControl, CacheDirective};
+pub use range::{Range, ByteRangeSpec, ContentRange, IfRange};
+pub use entity_tag::{EntityTag, IfNoneMatch, IfMatch};
+pub use cookie::Cookie;
+pub use date::{HttpDate, Date, LastModified, IfModifiedSince, IfUnmodifiedSince};
+
+pub trait TypedHeader: Sized + Send + Sync + 'static {
+    fn name() -> &'static str;
+
+    fn parse(value: &ByteSlice) -> Result<Self>;
+
+    fn write(&self, buf: &mut ByteArray);
+
+    fn to_bytearray(&self) -> ByteArray {
+        let mut buf = new_bytearray();
+        self.write(&mut buf);
+        buf
+    }
+}
+
+pub type HeaderParser = fn(&ByteSlice) -> Result<HeaderValue>;
+
+/// usable as a `HeaderParser`, e.g. `header::custom::<ContentType>`
+pub fn custom<H: TypedHeader>(value: &ByteSlice) -> Result<HeaderValue> {
+    let h = H::parse(value)?;
+
+    Ok(HeaderValue::Custom(Box::new(h)))
+}
+
+impl HeaderValue {
+    pub fn typed_ref<H: TypedHeader>(&self) -> Option<&H> {
+        match self {
+            HeaderValue::Custom(v) => v.downcast_ref::<H>(),
+            _ => None
+        }
+    }
+
+    pub fn from_typed<H: TypedHeader>(h: H) -> Self {
+        HeaderValue::Custom(Box::new(h))
+    }
+}
+
+impl Headers {
+    /// the header as `H`, whether it was stored raw or already parsed
+    pub fn typed<H: TypedHeader + Clone>(&self) -> Result<Option<H>> {
+        let value = match self.get(H::name().as_bytes()) {
+            Some(v) => v,
+            None => {
+                return Ok(None);
+            }
+        };
+
+        let h = match value {
+            HeaderValue::ByteArray(v) => H::parse(v)?,
+            HeaderValue::Utf8String(v) => H::parse(v.as_bytes())?,
+            HeaderValue::Integer(v) => H::parse(v.to_string().as_bytes())?,
+            HeaderValue::Custom(_) => {
+                match value.typed_ref::<H>() {
+                    Some(h) => h.clone(),
+                    None => {
+                        return Err(Error::Content(
+                                format!("{} holds an unexpected custom value", H::name())));
+                    }
+                }
+            }
+        };
+
+        Ok(Some(h))
+    }
+}
+
+/////////////////////////////
+/// a malformed header sent by the client, 400
+pub(crate) fn invalid(desc: String) -> Error {
+    Error::Detail(ErrorKind::BadRequest, desc)
+}
+
+pub(crate) fn is_token_char(c: u8) -> bool {
+    match c {
+        b'!' | b'#' | b'$' | b'%' | b'&' | b'\'' | b'*' | b'+' | b'-'
+            | b'.' | b'^' | b'_' | b'`' | b'|' | b'~' => true,
+        _ => c.is_ascii_alphanumeric()
+    }
+}
+
+pub(crate) fn is_token(s: &str) -> bool {
+    !s.is_empty() && s.bytes().all(is_token_char)
+}
+
+pub(crate) fn to_str<'a>(name: &str, value: &'a ByteSlice) -> Result<&'a str> {
+    match std::str::from_utf8(value) {
+        Ok(s) => Ok(s.trim()),
+        Err(_) => {
+            Err(invalid(
+                    format!("{} header value is not valid utf-8", name)))
+        }
+    }
+}
+
+/// splits on `sep` outside of quoted strings, skipping empty elements
+pub(crate) fn split_list(s: &str, sep: u8) -> Vec<&str> {
+    let mut items = Vec::new();
+    let mut start = 0;
+    let mut quoted = false;
+    let mut escaped = false;
+
+    for (i, c) in s.bytes().enumerate() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+
+        match c {
+            b'\\' if quoted => {
+                escaped = true;
+            },
+            b'"' => {
+                quoted = !quoted;
+            },
+            _ if c == sep && !quoted => {
+                let item = s[start..i].trim();
+                if !item.is_empty() {
+                    items.push(item);
+                }
+                start = i + 1;
+            },
+            _ => {}
+        }
+    }
+
+    let item = s[start..].trim();
+    if !item.is_empty() {
+        items.push(item);
+    }
+
+    items
+}
+
+pub(crate) fn unquote(s: &str) -> Result<String> {
+    let s = s.trim();
+
+    if !s.starts_with('"') {
+        return Ok(s.to_string());
+    }
+
+    if s.len() < 2 || !s.ends_with('"') {
+        return Err(invalid(
+                format!("unterminated quoted-string: {}", s)));
+    }
+
+    let mut r = String::with_capacity(s.len() - 2);
+    let mut escaped = false;
+
+    for c in s[1..s.len() - 1].chars() {
+        if escaped {
+            r.push(c);
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else {
+            r.push(c);
+        }
+    }
+
+    Ok(r)
+}
+
+pub(crate) fn write_maybe_quoted(value: &str, buf: &mut ByteArray) {
+    if is_token(value) {
+        buf.extend_from_slice(value.as_bytes());
+        return;
+    }
+
+    buf.push(b'"');
+    for c in value.bytes() {
+        if c == b'"' || c == b'\\' {
+            buf.push(b'\\');
+        }
+        buf.push(c);
+    }
+    buf.push(b'"');
+}
+
+/// parses `; name=value` parameters, names are lowercased
+pub(crate) fn parse_params(items: &[&str]) -> Result<Vec<(String, String)>> {
+    let mut params = Vec::with_capacity(items.len());
+
+    for item in items {
+        let (name, value) = match item.find('=') {
+            Some(i) => (item[..i].trim(), &item[i + 1..]),
+            None => {
+                return Err(invalid(
+                        format!("parameter without value: {}", item)));
+            }
+        };
+
+        if !is_token(name) {
+            return Err(invalid(
+                    format!("invalid parameter name: {}", name)));
+        }
+
+        params.push((name.to_ascii_lowercase(), unquote(value)?));
+    }
+
+    Ok(params)
+}
+
+pub(crate) fn write_params(params: &[(String, String)], buf: &mut ByteArray) {
+    for (name, value) in params {
+        buf.extend_from_slice(b"; ");
+        buf.extend_from_slice(name.as_bytes());
+        buf.push(b'=');
+        write_maybe_quoted(value, buf);
+    }
+}
@@ -0,0 +1,223 @@
+use crate::*;
+use crate::header::{Accept, QualityItem, to_str};
+use crate::header::accept::{parse_quality_list, MAX_QUALITY};
+
+/// `None` when the header is absent, which accepts anything
+fn quality_list(headers: &Headers, name: &str) -> Result<Option<Vec<QualityItem>>> {
+    let value = match headers.get(name.as_bytes()) {
+        Some(v) => v,
+        None => {
+            return Ok(None);
+        }
+    };
+
+    let list = match value {
+        HeaderValue::ByteArray(v) => parse_quality_list(to_str(name, v)?)?,
+        HeaderValue::Utf8String(v) => parse_quality_list(v)?,
+        HeaderValue::Custom(_) => {
+            match value.typed_ref::<Accept>() {
+                Some(a) => a.0.clone(),
+                None => {
+                    return Err(Error::Content(
+                            format!("{} holds an unexpected custom value", name)));
+                }
+            }
+        },
+        HeaderValue::Integer(_) => {
+            return Err(Error::Content(
+                    format!("{} holds an integer", name)));
+        }
+    };
+
+    Ok(Some(list))
+}
+
+/// the offer with the highest quality, earlier offers win ties;
+/// `quality` returns `None` when no range matches the offer
+fn best<'a, F>(offered: &[&'a str], name: &str, quality: F) -> Result<&'a str>
+    where F: Fn(&str) -> Option<u16> {
+    let mut chosen: Option<(&'a str, u16)> = None;
+
+    for offer in offered {
+        let q = match quality(offer) {
+            Some(q) if q > 0 => q,
+            _ => continue
+        };
+
+        match chosen {
+            Some((_, best_q)) if best_q >= q => {},
+            _ => {
+                chosen = Some((offer, q));
+            }
+        }
+    }
+
+    match chosen {
+        Some((offer, _)) => Ok(offer),
+        None => {
+            Err(Error::Detail(ErrorKind::NotAcceptable
+                    , format!("nothing acceptable for {}", name)))
+        }
+    }
+}
+
+fn no_offer(name: &str) -> Error {
+    Error::Detail(ErrorKind::NotAcceptable, format!("nothing offered for {}", name))
+}
+
+fn split_media(s: &str) -> (&str, &str) {
+    let s = match s.find(';') {
+        Some(i) => s[..i].trim(),
+        None => s.trim()
+    };
+
+    match s.find('/') {
+        Some(i) => (&s[..i], &s[i + 1..]),
+        None => (s, "")
+    }
+}
+
+/// picks from offered media types like `application/json` or `text/html`
+pub fn media_type<'a>(headers: &Headers, offered: &[&'a str]) -> Result<&'a str> {
+    let list = match quality_list(headers, "Accept")? {
+        Some(l) => l,
+        None => {
+            return offered.first().map(|o| *o).ok_or_else(|| no_offer("Accept"));
+        }
+    };
+
+    best(offered, "Accept", |offer| {
+        let (t, s) = split_media(offer);
+
+        // the most specific matching range decides
+        let mut matched: Option<(u8, u16)> = None;
+        for item in &list {
+            let (rt, rs) = split_media(&item.value);
+
+            let specificity = if rt == "*" && rs == "*" {
+                1
+            } else if rt.eq_ignore_ascii_case(t) && rs == "*" {
+                2
+            } else if rt.eq_ignore_ascii_case(t) && rs.eq_ignore_ascii_case(s) {
+                3 + std::cmp::min(item.params.len(), 250) as u8
+            } else {
+                continue;
+            };
+
+            match matched {
+                Some((sp, _)) if sp >= specificity => {},
+                _ => {
+                    matched = Some((specificity, item.quality));
+                }
+            }
+        }
+
+        matched.map(|(_, q)| q)
+    })
+}
+
+/// picks from offered codings like `br`, `gzip` or `identity`
+pub fn encoding<'a>(headers: &Headers, offered: &[&'a str]) -> Result<&'a str> {
+    let list = match quality_list(headers, "Accept-Encoding")? {
+        Some(l) => l,
+        None => {
+            return offered.first().map(|o| *o).ok_or_else(|| no_offer("Accept-Encoding"));
+        }
+    };
+
+    let normalize = |c: &str| -> String {
+        match c.to_ascii_lowercase().as_str() {
+            "x-gzip" => "gzip".to_string(),
+            "x-compress" => "compress".to_string(),
+            c => c.to_string()
+        }
+    };
+
+    best(offered, "Accept-Encoding", |offer| {
+        let offer = normalize(offer);
+
+        if let Some(item) = list.iter().find(|i| normalize(&i.value) == offer) {
+            return Some(item.quality);
+        }
+
+        if let Some(item) = list.iter().find(|i| i.value == "*") {
+            return Some(item.quality);
+        }
+
+        // identity is acceptable unless excluded explicitly or through `*`
+        if offer == "identity" {
+            return Some(MAX_QUALITY);
+        }
+
+        None
+    })
+}
+
+/// picks from offered language tags like `en-US`, with RFC 4647 basic filtering
+pub fn language<'a>(headers: &Headers, offered: &[&'a str]) -> Result<&'a str> {
+    let list = match quality_list(headers, "Accept-Language")? {
+        Some(l) => l,
+        None => {
+            return offered.first().map(|o| *o).ok_or_else(|| no_offer("Accept-Language"));
+        }
+    };
+
+    best(offered, "Accept-Language", |offer| {
+        let offer = offer.to_ascii_lowercase();
+
+        let mut matched: Option<(usize, u16)> = None;
+        for item in &list {
+            let range = item.value.to_ascii_lowercase();
+
+            let specificity = if range == "*" {
+                0
+            } else if offer == range
+                || (offer.starts_with(&range) && offer.as_bytes()[range.len()] == b'-') {
+                range.len()
+            } else {
+                continue;
+            };
+
+            match matched {
+                Some((sp, _)) if sp >= specificity => {},
+                _ => {
+                    matched = Some((specificity, item.quality));
+                }
+            }
+        }
+
+        matched.map(|(_, q)| q)
+    })
+}
+
+/// picks from offered charsets like `utf-8`
+pub fn charset<'a>(headers: &Headers, offered: &[&'a str]) -> Result<&'a str> {
+    let list = match quality_list(headers, "Accept-Charset")? {
+        Some(l) => l,
+        None => {
+            return offered.first().map(|o| *o).ok_or_else(|| no_offer("Accept-Charset"));
+        }
+    };
+
+    best(offered, "Accept-Charset", |offer| {
+        if let Some(item) = list.iter().find(|i| i.value.eq_ignore_ascii_case(offer)) {
+            return Some(item.quality);
+        }
+
+        list.iter().find(|i| i.value == "*").map(|i| i.quality)
+    })
+}
+
+/// `Accept` negotiation over `body::Format` representations
+pub fn format(headers: &Headers, offered: &[body::Format]) -> Result<body::Format> {
+    let types: Vec<String> = offered.iter()
+        .map(|f| f.content_type().mime().to_string())
+        .collect();
+    let refs: Vec<&str> = types.iter().map(|s| s.as_str()).collect();
+
+    let chosen = media_type(headers, &refs)?;
+    let i = refs.iter().position(|r| *r == chosen).unwrap();
+
+    Ok(offered[i])
+}
+
@@ -0,0 +1,264 @@
+use crate::*;
+use crate::header::*;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ByteRangeSpec {
+    /// `first-last`, both inclusive
+    FromTo(u64, u64),
+    /// `first-`
+    From(u64),
+    /// `-suffix_length`
+    Last(u64)
+}
+
+impl ByteRangeSpec {
+    /// inclusive `(first, last)` within a representation of `len` bytes
+    pub fn to_satisfiable(&self, len: u64) -> Option<(u64, u64)> {
+        if len == 0 {
+            return None;
+        }
+
+        match *self {
+            ByteRangeSpec::FromTo(first, last) => {
+                if first >= len {
+                    None
+                } else {
+                    Some((first, std::cmp::min(last, len - 1)))
+                }
+            },
+            ByteRangeSpec::From(first) => {
+                if first >= len {
+                    None
+                } else {
+                    Some((first, len - 1))
+                }
+            },
+            ByteRangeSpec::Last(suffix) => {
+                if suffix == 0 {
+                    None
+                } else {
+                    Some((len - std::cmp::min(suffix, len), len - 1))
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Range(pub Vec<ByteRangeSpec>);
+
+impl Range {
+    pub fn specs(&self) -> &[ByteRangeSpec] {
+        &self.0
+    }
+
+    pub fn bytes(first: u64, last: Option<u64>) -> Self {
+        match last {
+            Some(last) => Range(vec![ByteRangeSpec::FromTo(first, last)]),
+            None => Range(vec![ByteRangeSpec::From(first)])
+        }
+    }
+}
+
+fn parse_pos(s: &str) -> Result<u64> {
+    if s.is_empty() || !s.bytes().all(|c| c.is_ascii_digit()) {
+        return Err(invalid(
+                format!("Range invalid position: {}", s)));
+    }
+
+    match s.parse::<u64>() {
+        Ok(n) => Ok(n),
+        Err(_) => {
+            Err(invalid(
+                    format!("Range position overflow: {}", s)))
+        }
+    }
+}
+
+impl TypedHeader for Range {
+    fn name() -> &'static str {
+        "Range"
+    }
+
+    fn parse(value: &ByteSlice) -> Result<Self> {
+        let value = to_str(Self::name(), value)?;
+
+        let specs = match value.find('=') {
+            Some(i) if value[..i].trim().eq_ignore_ascii_case("bytes") => &value[i + 1..],
+            _ => {
+                return Err(invalid(
+                        format!("Range unsupported unit: {}", value)));
+            }
+        };
+
+        let mut ranges = Vec::new();
+
+        for spec in split_list(specs, b',') {
+            let i = match spec.find('-') {
+                Some(i) => i,
+                None => {
+                    return Err(invalid(
+                            format!("Range invalid spec: {}", spec)));
+                }
+            };
+
+            let (first, last) = (spec[..i].trim(), spec[i + 1..].trim());
+
+            let r = if first.is_empty() {
+                ByteRangeSpec::Last(parse_pos(last)?)
+            } else if last.is_empty() {
+                ByteRangeSpec::From(parse_pos(first)?)
+            } else {
+                let (first, last) = (parse_pos(first)?, parse_pos(last)?);
+                if last < first {
+                    return Err(invalid(
+                            format!("Range last < first: {}", spec)));
+                }
+                ByteRangeSpec::FromTo(first, last)
+            };
+
+            ranges.push(r);
+        }
+
+        if ranges.is_empty() {
+            return Err(invalid(
+                    format!("Range without specs")));
+        }
+
+        Ok(Range(ranges))
+    }
+
+    fn write(&self, buf: &mut ByteArray) {
+        buf.extend_from_slice(b"bytes=");
+
+        for (i, r) in self.0.iter().enumerate() {
+            if i > 0 {
+                buf.push(b',');
+            }
+
+            let s = match r {
+                ByteRangeSpec::FromTo(first, last) => format!("{}-{}", first, last),
+                ByteRangeSpec::From(first) => format!("{}-", first),
+                ByteRangeSpec::Last(suffix) => format!("-{}", suffix)
+            };
+
+            buf.extend_from_slice(s.as_bytes());
+        }
+    }
+}
+
+/////////////////////////////
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ContentRange {
+    /// `bytes first-last/complete`, `*` for an unknown complete length
+    Bytes {
+        first: u64,
+        last: u64,
+        complete: Option<u64>
+    },
+    /// `bytes */complete`, sent with 416
+    Unsatisfied(u64)
+}
+
+impl TypedHeader for ContentRange {
+    fn name() -> &'static str {
+        "Content-Range"
+    }
+
+    fn parse(value: &ByteSlice) -> Result<Self> {
+        let value = to_str(Self::name(), value)?;
+
+        let err = || invalid(format!("Content-Range invalid: {}", value));
+
+        let rest = match value.find(' ') {
+            Some(i) if value[..i].eq_ignore_ascii_case("bytes") => value[i + 1..].trim(),
+            _ => {
+                return Err(err());
+            }
+        };
+
+        let (range, complete) = match rest.find('/') {
+            Some(i) => (&rest[..i], &rest[i + 1..]),
+            None => {
+                return Err(err());
+            }
+        };
+
+        if range == "*" {
+            return Ok(ContentRange::Unsatisfied(parse_pos(complete)?));
+        }
+
+        let complete = if complete == "*" { None } else { Some(parse_pos(complete)?) };
+
+        let (first, last) = match range.find('-') {
+            Some(i) => (parse_pos(&range[..i])?, parse_pos(&range[i + 1..])?),
+            None => {
+                return Err(err());
+            }
+        };
+
+        if last < first || complete.map(|c| last >= c).unwrap_or(false) {
+            return Err(err());
+        }
+
+        Ok(ContentRange::Bytes {
+            first: first,
+            last: last,
+            complete: complete
+        })
+    }
+
+    fn write(&self, buf: &mut ByteArray) {
+        let s = match self {
+            ContentRange::Bytes { first, last, complete: Some(c) } => {
+                format!("bytes {}-{}/{}", first, last, c)
+            },
+            ContentRange::Bytes { first, last, complete: None } => {
+                format!("bytes {}-{}/*", first, last)
+            },
+            ContentRange::Unsatisfied(c) => format!("bytes */{}", c)
+        };
+
+        buf.extend_from_slice(s.as_bytes());
+    }
+}
+
+/////////////////////////////
+#[derive(Debug, Clone, PartialEq)]
+pub enum IfRange {
+    Tag(EntityTag),
+    Date(HttpDate)
+}
+
+impl IfRange {
+    /// a strong match against the current validators, RFC 9110 13.1.5
+    pub fn matches(&self, etag: Option<&EntityTag>, last_modified: Option<HttpDate>) -> bool {
+        match self {
+            IfRange::Tag(t) => etag.map(|e| t.strong_eq(e)).unwrap_or(false),
+            IfRange::Date(d) => last_modified.map(|m| m == *d).unwrap_or(false)
+        }
+    }
+}
+
+impl TypedHeader for IfRange {
+    fn name() -> &'static str {
+        "If-Range"
+    }
+
+    fn parse(value: &ByteSlice) -> Result<Self> {
+        let value = to_str(Self::name(), value)?;
+
+        if value.starts_with('"') || value.starts_with("W/") {
+            Ok(IfRange::Tag(EntityTag::parse_one(value)?))
+        } else {
+            Ok(IfRange::Date(HttpDate::parse_str(value)?))
+        }
+    }
+
+    fn write(&self, buf: &mut ByteArray) {
+        match self {
+            IfRange::Tag(t) => t.write(buf),
+            IfRange::Date(d) => d.write(buf)
+        }
+    }
+}
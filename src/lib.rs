@@ -1,4 +1,8 @@
 pub mod stream_handler;
+pub mod header;
+pub mod body;
+pub mod response;
+pub mod static_files;
 // pub mod route;
 
 pub type Any = Box<dyn std::any::Any>;
@@ -8,7 +12,52 @@ type AnyResult = std::result::Result<Any, String>;
 type DescResult = std::result::Result<(), String>;
 
 pub enum Error {
-    Content(String)
+    Content(String),
+    Simple(ErrorKind),
+    Detail(ErrorKind, String)
+}
+
+impl Error {
+    pub fn status(&self) -> u16 {
+        match self {
+            Error::Content(_) => 500,
+            Error::Simple(kind) => kind.status(),
+            Error::Detail(kind, _) => kind.status()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    RouteIsEmpty,
+    NotMatched,
+    MethodIsExist,
+    BadRequest,
+    PayloadTooLarge,
+    UriTooLong,
+    NotAcceptable,
+    UnsupportedMediaType,
+    HeaderFieldsTooLarge,
+    NotImplemented,
+    Forbidden
+}
+
+impl ErrorKind {
+    pub fn status(&self) -> u16 {
+        match self {
+            ErrorKind::RouteIsEmpty => 404,
+            ErrorKind::NotMatched => 404,
+            ErrorKind::MethodIsExist => 500,
+            ErrorKind::BadRequest => 400,
+            ErrorKind::PayloadTooLarge => 413,
+            ErrorKind::NotAcceptable => 406,
+            ErrorKind::UriTooLong => 414,
+            ErrorKind::UnsupportedMediaType => 415,
+            ErrorKind::HeaderFieldsTooLarge => 431,
+            ErrorKind::NotImplemented => 501,
+            ErrorKind::Forbidden => 403
+        }
+    }
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -29,18 +78,99 @@ pub fn new_bytearray() -> ByteArray {
 #[derive(Clone)]
 pub struct RegisterOptions {
     pub header_value_type: HeaderValueType,
-    pub body_type: BodyType
+    pub body_type: BodyType,
+    pub parse_mode: ParseMode,
+    /// how `BodyType::Utf8String` and `BodyType::UrlEncoded` treat bytes invalid in the body charset
+    pub charset_mode: body::CharsetMode,
+    /// overrides the stream limits for the header and body of this route
+    pub limits: Option<Limits>,
+    /// where `BodyType::Spool` writes its temp files, `std::env::temp_dir()` when unset
+    pub spool_dir: Option<std::path::PathBuf>
 }
 
 impl Default for RegisterOptions {
     fn default() -> Self {
         Self {
             header_value_type: HeaderValueType::Utf8String,
-            body_type: BodyType::Utf8String
+            body_type: BodyType::Utf8String,
+            parse_mode: ParseMode::Strict,
+            charset_mode: body::CharsetMode::Strict,
+            limits: None,
+            spool_dir: None
         }
     }
 }
 
+impl RegisterOptions {
+    pub fn limits_or(&self, global: &Limits) -> Limits {
+        match &self.limits {
+            Some(l) => *l,
+            None => *global
+        }
+    }
+
+    pub fn spool_dir_or_temp(&self) -> std::path::PathBuf {
+        match &self.spool_dir {
+            Some(d) => d.clone(),
+            None => std::env::temp_dir()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Limits {
+    /// 414 when exceeded
+    pub request_line: usize,
+    /// a single header line, 431 when exceeded
+    pub header_size: usize,
+    /// 431 when exceeded
+    pub header_count: usize,
+    /// all header lines together, 431 when exceeded
+    pub header_bytes: usize,
+    /// 413 when exceeded, for a compressed body also the decoded size
+    pub body_size: usize,
+    /// decoded bytes per received byte a compressed body may reach, 413 past it
+    pub decompress_ratio: usize
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            request_line: 8 * 1024,
+            header_size: 8 * 1024,
+            header_count: 100,
+            header_bytes: 64 * 1024,
+            body_size: 8 * 1024 * 1024,
+            decompress_ratio: 100
+        }
+    }
+}
+
+impl Limits {
+    pub fn check_body_size(&self, size: usize) -> Result<()> {
+        if size > self.body_size {
+            return Err(Error::Detail(ErrorKind::PayloadTooLarge
+                    , format!("body size {} > limit {}", size, self.body_size)));
+        }
+
+        Ok(())
+    }
+}
+
+/// Strict rejects bare LF, obs-fold, whitespace before the colon and
+/// invalid token characters; Lenient accepts them like common servers do
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseMode {
+    Strict,
+    Lenient
+}
+
+impl Default for ParseMode {
+    fn default() -> Self {
+        ParseMode::Strict
+    }
+}
+
 ////////////////////////////
 pub struct Context {
     pub data: SendAny
@@ -59,7 +189,18 @@ pub enum HeaderValueType {
 /////////////////////////////
 pub enum BodyType {
     ByteArray,
+    /// decoded from the Content-Type charset, utf-8 when absent
     Utf8String,
+    /// the handler reads the body itself through `Body::Stream`
+    Stream,
+    /// `Body::Spool`, in memory up to the threshold in bytes, a temp file past it
+    Spool(usize),
+    /// `application/x-www-form-urlencoded` into `Body::Form`
+    UrlEncoded,
+    /// deserialized by the given decoder into `Body::Json`, see `body::json::decoder`
+    Json(body::json::JsonDecoder),
+    /// JSON, MessagePack or CBOR by Content-Type into `Body::Structured`, see `body::structured::decoder`
+    Structured(body::structured::StructuredDecoder),
     Custom
 }
 
@@ -70,6 +211,7 @@ pub enum Method {
     Put,
     Get,
     Delete,
+    Head,
     Unknown
 }
 
@@ -79,6 +221,19 @@ impl Default for Method {
     }
 }
 
+impl Method {
+    pub fn from_bytes(v: &ByteSlice) -> Self {
+        match v {
+            b"POST" => Method::Post,
+            b"PUT" => Method::Put,
+            b"GET" => Method::Get,
+            b"DELETE" => Method::Delete,
+            b"HEAD" => Method::Head,
+            _ => Method::Unknown
+        }
+    }
+}
+
 #[derive(Debug, Default)]
 pub struct Version(ByteArray);
 
@@ -148,6 +303,15 @@ impl Headers {
         Some(&self.indexs[*key])
     }
 
+    /// header names are matched case-insensitively
+    pub fn insert(&mut self, name: &ByteSlice, value: HeaderValue) {
+        self.bytearrays.insert(name.to_ascii_lowercase(), value);
+    }
+
+    pub fn get(&self, name: &ByteSlice) -> Option<&HeaderValue> {
+        self.bytearrays.get(&name.to_ascii_lowercase())
+    }
+
     fn new() -> Self {
         Self {
             indexs: Vec::new(),
@@ -161,7 +325,26 @@ impl Headers {
 pub enum Body {
     ByteArray(Vec<u8>),
     Utf8String(String),
-    Custom(SendAny)
+    Stream(stream_handler::body::BodyStream),
+    Spool(stream_handler::spool::SpoolBody),
+    Form(body::FormData),
+    Json(SendAny),
+    Structured(body::Format, SendAny),
+    Custom(SendAny),
+    /// a response body produced while it is sent, written with chunked framing;
+    /// an `Err` aborts the connection instead of ending the body
+    Channel(tokio::sync::mpsc::Receiver<Result<ByteArray>>),
+    File(response::sendfile::FileBody),
+    /// bytes compiled into the binary, e.g. embedded assets
+    Static(&'static ByteSlice),
+    /// bytes shared with a cache, sent without copying
+    Shared(std::sync::Arc<ByteSlice>)
+}
+
+impl Default for Body {
+    fn default() -> Self {
+        Body::ByteArray(new_none_bytearray())
+    }
 }
 
 #[derive(Default)]
@@ -180,7 +363,7 @@ pub type SharedResponseSender = std::sync::Arc<tokio::sync::RwLock<tokio::sync::
 
 pub struct ResponseCaptial {
     version: ByteArray,
-    status: u16,
+    status: response::StatusCode,
     status_desc: ByteArray
 }
 
@@ -188,26 +371,97 @@ impl Default for ResponseCaptial {
     fn default() -> Self {
         Self {
             version: "HTTP/1.1".as_bytes().to_vec(),
-            status: 200,
+            status: response::StatusCode::OK,
             status_desc: "OK".as_bytes().to_vec()
         }
     }
 }
 
+impl ResponseCaptial {
+    pub fn new(status: response::StatusCode) -> Self {
+        let mut captial = Self::default();
+        captial.set_status(status);
+        captial
+    }
+
+    pub fn status(&self) -> response::StatusCode {
+        self.status
+    }
+
+    /// unregistered codes go out with an empty reason phrase
+    pub fn set_status(&mut self, status: response::StatusCode) {
+        self.status = status;
+        self.status_desc = status.reason().unwrap_or("").as_bytes().to_vec();
+    }
+
+    /// reason-phrase is HTAB, SP, VCHAR and obs-text, a CR or LF would
+    /// end the status line early
+    pub fn set_reason(&mut self, reason: &str) -> Result<()> {
+        if let Some(c) = reason.bytes().find(|c| (*c < 0x20 && *c != b'\t') || *c == 0x7f) {
+            return Err(Error::Content(
+                    format!("invalid byte 0x{:02x} in reason phrase", c)));
+        }
+
+        self.status_desc = reason.as_bytes().to_vec();
+
+        Ok(())
+    }
+
+    pub fn set_version(&mut self, version: &ByteSlice) {
+        self.version = version.to_vec();
+    }
+}
+
+/// raw field lines without validation, see `ResponseHeaders::unchecked_mut`
 #[derive(Default)]
 pub struct ResponseUncheckHeaders {
     bytes: ByteArray
 }
 
+impl ResponseUncheckHeaders {
+    pub fn push(&mut self, name: &ByteSlice, value: &ByteSlice) {
+        self.bytes.extend_from_slice(name);
+        self.bytes.extend_from_slice(b": ");
+        self.bytes.extend_from_slice(value);
+        self.bytes.extend_from_slice(b"\r\n");
+    }
+
+    pub fn push_typed<H: header::TypedHeader>(&mut self, h: &H) {
+        self.bytes.extend_from_slice(H::name().as_bytes());
+        self.bytes.extend_from_slice(b": ");
+        h.write(&mut self.bytes);
+        self.bytes.extend_from_slice(b"\r\n");
+    }
+}
+
 #[derive(Default)]
 pub struct ResponseContent {
     pub captial: ResponseCaptial,
-    pub headers: ResponseUncheckHeaders,
+    pub headers: response::ResponseHeaders,
     pub body: Body
 }
 
+pub struct ResponseOptions {
+    /// the `Server` header, omitted when `None`
+    pub server: Option<String>,
+    /// the `Date` header from the cached clock
+    pub date: bool
+}
+
+impl Default for ResponseOptions {
+    fn default() -> Self {
+        Self {
+            server: Some("tokio-httpserver".to_string()),
+            date: true
+        }
+    }
+}
+
 pub struct Response<Writer: tokio::io::AsyncWrite + Send> {
     writer: std::sync::Arc<tokio::sync::RwLock<Writer>>,
-    content: ResponseContent
+    content: ResponseContent,
+    options: std::sync::Arc<ResponseOptions>,
+    // HEAD: the head of the GET response without its body
+    head_only: bool
 }
 
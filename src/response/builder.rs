@@ -0,0 +1,140 @@
+use crate::*;
+use crate::header::{TypedHeader, ContentType};
+use super::{StatusCode, ResponseHeaders};
+
+/// fluent construction of a `ResponseContent`, e.g.
+/// `ResponseBuilder::new().status(StatusCode::CREATED).text("done").build()?`
+#[derive(Default)]
+pub struct ResponseBuilder {
+    captial: ResponseCaptial,
+    headers: ResponseHeaders,
+    body: Body,
+    body_etag: bool,
+    // the first invalid header, reported by `build`
+    error: Option<Error>
+}
+
+impl ResponseBuilder {
+    pub fn status(mut self, status: StatusCode) -> Self {
+        self.captial.set_status(status);
+        self
+    }
+
+    /// overrides the canonical reason phrase of the status
+    pub fn reason(mut self, reason: &str) -> Self {
+        let r = self.captial.set_reason(reason);
+        self.check(r)
+    }
+
+    fn check(mut self, r: Result<()>) -> Self {
+        if let Err(e) = r {
+            if self.error.is_none() {
+                self.error = Some(e);
+            }
+        }
+        self
+    }
+
+    /// replaces an earlier value of the same header
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        let r = self.headers.set(name, value.as_bytes());
+        self.check(r)
+    }
+
+    /// adds another field line, e.g. for Set-Cookie
+    pub fn append_header(mut self, name: &str, value: &str) -> Self {
+        let r = self.headers.append(name, value.as_bytes());
+        self.check(r)
+    }
+
+    pub fn typed_header<H: TypedHeader>(mut self, h: &H) -> Self {
+        let r = self.headers.set_typed(h);
+        self.check(r)
+    }
+
+    /// written as is, without validation
+    pub fn header_unchecked(mut self, name: &str, value: &ByteSlice) -> Self {
+        self.headers.unchecked_mut().push(name.as_bytes(), value);
+        self
+    }
+
+    pub fn content_type(self, ct: &ContentType) -> Self {
+        self.typed_header(ct)
+    }
+
+    pub fn body(mut self, body: Body) -> Self {
+        self.body = body;
+        self
+    }
+
+    pub fn bytes(self, v: ByteArray) -> Self {
+        self.body(Body::ByteArray(v))
+    }
+
+    /// `text/plain; charset=utf-8`
+    pub fn text(self, s: &str) -> Self {
+        self.content_type(&ContentType::text_plain())
+            .body(Body::Utf8String(s.to_string()))
+    }
+
+    /// `text/html; charset=utf-8`
+    pub fn html(self, s: &str) -> Self {
+        self.content_type(&ContentType::text_html())
+            .body(Body::Utf8String(s.to_string()))
+    }
+
+    /// serialized with the matching Content-Type
+    pub fn encoded<T: serde::Serialize + ?Sized>(self, format: body::Format, value: &T) -> Result<Self> {
+        let bytes = body::structured::encode(format, value)?;
+
+        Ok(self.content_type(&format.content_type()).bytes(bytes))
+    }
+
+    /// a strong ETag from the final body at `build`, when it is buffered
+    /// and no ETag was set
+    pub fn etag_from_body(mut self) -> Self {
+        self.body_etag = true;
+        self
+    }
+
+    pub fn json<T: serde::Serialize + ?Sized>(self, value: &T) -> Result<Self> {
+        self.encoded(body::Format::Json, value)
+    }
+
+    /// fails with the first invalid reason phrase, header name or value
+    pub fn build(self) -> Result<ResponseContent> {
+        if let Some(e) = self.error {
+            return Err(e);
+        }
+
+        let mut content = ResponseContent {
+            captial: self.captial,
+            headers: self.headers,
+            body: self.body
+        };
+
+        if self.body_etag {
+            content.set_body_etag()?;
+        }
+
+        Ok(content)
+    }
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// the status of `err` as a plain text body; the message is kept only for
+    /// a 4xx other than 403, where it describes the client's own request, any
+    /// other status gets its reason phrase so server internals stay private
+    pub fn from_error(err: &Error) -> Self {
+        let status = StatusCode::from(err);
+
+        let message = match err {
+            Error::Detail(_, s) if status.is_client_error() && status != StatusCode::FORBIDDEN => s.clone(),
+            _ => status.reason().unwrap_or("Error").to_string()
+        };
+
+        Self::new().status(status).text(&message)
+    }
+}
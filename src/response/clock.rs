@@ -0,0 +1,32 @@
+use std::sync::RwLock;
+use lazy_static::lazy_static;
+
+use crate::*;
+use crate::header::HttpDate;
+
+lazy_static! {
+    // (unix seconds, IMF-fixdate)
+    static ref CACHED: RwLock<(u64, ByteArray)> = RwLock::new((0, new_none_bytearray()));
+}
+
+/// the current `Date` value, formatted at most once per second
+pub fn date_bytes() -> ByteArray {
+    let now = HttpDate::now();
+
+    if let Ok(cached) = CACHED.read() {
+        if cached.0 == now.secs() {
+            return cached.1.clone();
+        }
+    }
+
+    let mut buf = new_bytearray();
+    now.write(&mut buf);
+
+    if let Ok(mut cached) = CACHED.write() {
+        if cached.0 < now.secs() {
+            *cached = (now.secs(), buf.clone());
+        }
+    }
+
+    buf
+}
@@ -0,0 +1,352 @@
+use std::io::Write;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+use crate::*;
+use crate::header::{TypedHeader, EntityTag, negotiate};
+use crate::stream_handler::body::BodyStream;
+use crate::stream_handler::spool::{SpoolBody, TempFile};
+use super::{StatusCode, FileBody};
+
+/// codings in the order they are preferred when the client weighs them equally
+const CODINGS: &[&str] = &["br", "gzip", "deflate"];
+
+pub struct CompressOptions {
+    /// smaller bodies are sent as they are; bodies of unknown length are
+    /// always compressed
+    pub min_size: u64,
+    /// `text/*` matches every subtype, parameters are ignored
+    pub mime_types: Vec<String>,
+    /// 0-9, for gzip and deflate
+    pub level: u32,
+    /// 0-11
+    pub brotli_quality: u32
+}
+
+impl Default for CompressOptions {
+    fn default() -> Self {
+        let mime_types = [
+            "text/*",
+            "application/json",
+            "application/javascript",
+            "application/xml",
+            "application/manifest+json",
+            "application/wasm",
+            "image/svg+xml"
+        ];
+
+        Self {
+            min_size: 1024,
+            mime_types: mime_types.iter().map(|m| m.to_string()).collect(),
+            level: 6,
+            brotli_quality: 5
+        }
+    }
+}
+
+impl CompressOptions {
+    pub fn allows(&self, content_type: &ByteSlice) -> bool {
+        let ct = match std::str::from_utf8(content_type) {
+            Ok(s) => s,
+            Err(_) => {
+                return false;
+            }
+        };
+        let ct = match ct.find(';') {
+            Some(i) => &ct[..i],
+            None => ct
+        };
+        let ct = ct.trim().to_ascii_lowercase();
+
+        self.mime_types.iter().any(|m| {
+            match m.strip_suffix("/*") {
+                Some(top) => ct.split('/').next() == Some(top),
+                None => ct == *m
+            }
+        })
+    }
+}
+
+enum Encoder {
+    Gzip(flate2::write::GzEncoder<ByteArray>),
+    Deflate(flate2::write::ZlibEncoder<ByteArray>),
+    Brotli(Box<brotli::CompressorWriter<ByteArray>>)
+}
+
+fn compress_error(e: std::io::Error) -> Error {
+    Error::Content(format!("failed to compress response: {:?}", e))
+}
+
+impl Encoder {
+    fn new(coding: &str, options: &CompressOptions) -> Self {
+        let level = flate2::Compression::new(std::cmp::min(options.level, 9));
+
+        match coding {
+            "br" => {
+                Encoder::Brotli(Box::new(brotli::CompressorWriter::new(
+                            new_bytearray(), 4096, std::cmp::min(options.brotli_quality, 11), 22)))
+            },
+            "gzip" => Encoder::Gzip(flate2::write::GzEncoder::new(new_bytearray(), level)),
+            _ => Encoder::Deflate(flate2::write::ZlibEncoder::new(new_bytearray(), level))
+        }
+    }
+
+    fn output(&mut self) -> ByteArray {
+        match self {
+            Encoder::Gzip(e) => std::mem::take(e.get_mut()),
+            Encoder::Deflate(e) => std::mem::take(e.get_mut()),
+            Encoder::Brotli(e) => std::mem::take(e.get_mut())
+        }
+    }
+
+    /// what the encoder has produced so far for `data`
+    fn write(&mut self, data: &ByteSlice) -> Result<ByteArray> {
+        let r = match self {
+            Encoder::Gzip(e) => e.write_all(data),
+            Encoder::Deflate(e) => e.write_all(data),
+            Encoder::Brotli(e) => e.write_all(data)
+        };
+        r.map_err(compress_error)?;
+
+        Ok(self.output())
+    }
+
+    /// everything written so far, decodable by the client on its own
+    fn flush(&mut self) -> Result<ByteArray> {
+        let r = match self {
+            Encoder::Gzip(e) => e.flush(),
+            Encoder::Deflate(e) => e.flush(),
+            Encoder::Brotli(e) => e.flush()
+        };
+        r.map_err(compress_error)?;
+
+        Ok(self.output())
+    }
+
+    fn finish(self) -> Result<ByteArray> {
+        match self {
+            Encoder::Gzip(e) => e.finish().map_err(compress_error),
+            Encoder::Deflate(e) => e.finish().map_err(compress_error),
+            Encoder::Brotli(e) => Ok(e.into_inner())
+        }
+    }
+}
+
+/// a body whose chunks are compressed as they arrive
+enum Source {
+    Stream(BodyStream),
+    Channel(tokio::sync::mpsc::Receiver<Result<ByteArray>>),
+    File(FileBody),
+    Spool(TempFile)
+}
+
+fn read_error(e: std::io::Error) -> Error {
+    Error::Content(format!("failed to read response body: {:?}", e))
+}
+
+/// the next chunk of at most 64K, `None` once `remaining` is read; the
+/// file ending before that is an error
+async fn read_chunk(file: &mut tokio::fs::File, remaining: &mut u64) -> Result<Option<ByteArray>> {
+    if *remaining == 0 {
+        return Ok(None);
+    }
+
+    let mut buf = vec![0u8; std::cmp::min(*remaining, 64 * 1024) as usize];
+    let n = file.read(&mut buf).await.map_err(read_error)?;
+    if n == 0 {
+        return Err(Error::Content(format!("response body file ended early")));
+    }
+    buf.truncate(n);
+    *remaining -= n as u64;
+
+    Ok(Some(buf))
+}
+
+/// feeds `source` through the encoder into `tx`; chunks from a channel are
+/// flushed one by one so streamed events are not held back, an error is
+/// sent in place of the rest
+async fn encode_body(source: Source, mut encoder: Encoder, tx: tokio::sync::mpsc::Sender<Result<ByteArray>>) {
+    let r: Result<bool> = async {
+        match source {
+            Source::Stream(mut body) => {
+                while let Some(chunk) = body.next_chunk().await? {
+                    if tx.send(Ok(encoder.write(&chunk)?)).await.is_err() {
+                        return Ok(false);
+                    }
+                }
+            },
+            Source::Channel(mut rx) => {
+                while let Some(chunk) = rx.recv().await {
+                    encoder.write(&chunk?)?;
+                    if tx.send(Ok(encoder.flush()?)).await.is_err() {
+                        return Ok(false);
+                    }
+                }
+            },
+            Source::File(f) => {
+                let (mut file, offset, mut remaining) = f.into_parts();
+                file.seek(std::io::SeekFrom::Start(offset)).await.map_err(read_error)?;
+                while let Some(chunk) = read_chunk(&mut file, &mut remaining).await? {
+                    if tx.send(Ok(encoder.write(&chunk)?)).await.is_err() {
+                        return Ok(false);
+                    }
+                }
+            },
+            Source::Spool(f) => {
+                let mut file = f.open().await?;
+                let mut remaining = f.len();
+                while let Some(chunk) = read_chunk(&mut file, &mut remaining).await? {
+                    if tx.send(Ok(encoder.write(&chunk)?)).await.is_err() {
+                        return Ok(false);
+                    }
+                }
+            }
+        }
+
+        Ok(true)
+    }.await;
+
+    let out = match r {
+        Ok(true) => encoder.finish(),
+        Ok(false) => {
+            return;
+        },
+        Err(e) => Err(e)
+    };
+    let _ = tx.send(out).await;
+}
+
+/// adds `Accept-Encoding` to Vary unless it is listed or Vary is `*`
+fn vary_accept_encoding(headers: &mut super::ResponseHeaders) -> Result<()> {
+    let listed = headers.get_all("Vary").any(|v| {
+        String::from_utf8_lossy(v).split(',').any(|t| {
+            let t = t.trim();
+            t == "*" || t.eq_ignore_ascii_case("Accept-Encoding")
+        })
+    });
+
+    if !listed {
+        headers.append("Vary", b"Accept-Encoding")?;
+    }
+
+    Ok(())
+}
+
+fn memory_body(body: &Body) -> Option<&ByteSlice> {
+    match body {
+        Body::ByteArray(v) => Some(v),
+        Body::Utf8String(v) => Some(v.as_bytes()),
+        Body::Static(v) => Some(v),
+        Body::Shared(v) => Some(v),
+        Body::Spool(SpoolBody::Memory(v)) => Some(v),
+        _ => None
+    }
+}
+
+fn body_len(body: &Body) -> Option<u64> {
+    match body {
+        Body::File(f) => Some(f.len()),
+        Body::Spool(f) => Some(f.len()),
+        Body::Stream(s) => s.len().map(|n| n as u64),
+        Body::Channel(_) => None,
+        body => memory_body(body).map(|v| v.len() as u64)
+    }
+}
+
+/// compresses a 2xx response with the best coding `Accept-Encoding` allows:
+/// bodies already in memory at once, others into a chunked body as they are
+/// read; responses with a Content-Encoding or Content-Range, or a type
+/// outside the allowlist, are left alone
+pub fn apply(request: &RequestHeader, mut content: ResponseContent, options: &CompressOptions)
+    -> Result<ResponseContent> {
+    let status = content.captial.status();
+    if !status.is_success() || status == StatusCode::NO_CONTENT
+        || status == StatusCode::PARTIAL_CONTENT {
+        return Ok(content);
+    }
+
+    if content.headers.contains("Content-Encoding") || content.headers.contains("Content-Range") {
+        return Ok(content);
+    }
+
+    match content.headers.get("Content-Type") {
+        Some(ct) if options.allows(ct) => {},
+        _ => {
+            return Ok(content);
+        }
+    }
+
+    // the representation depends on the header whichever coding is chosen
+    vary_accept_encoding(&mut content.headers)?;
+
+    match body_len(&content.body) {
+        Some(n) if n < options.min_size => {
+            return Ok(content);
+        },
+        _ => {}
+    }
+
+    if request.headers.get(b"Accept-Encoding").is_none() {
+        return Ok(content);
+    }
+
+    let mut offered = CODINGS.to_vec();
+    offered.push("identity");
+    let coding = match negotiate::encoding(&request.headers, &offered) {
+        Ok(c) if c != "identity" => c,
+        _ => {
+            return Ok(content);
+        }
+    };
+
+    let mut encoder = Encoder::new(coding, options);
+
+    let source = match content.body {
+        Body::Stream(s) => Source::Stream(s),
+        Body::Channel(rx) => Source::Channel(rx),
+        Body::File(f) => Source::File(f),
+        Body::Spool(SpoolBody::File(f)) => Source::Spool(f),
+        body => {
+            let data = match memory_body(&body) {
+                Some(v) => v,
+                None => {
+                    content.body = body;
+                    return Ok(content);
+                }
+            };
+
+            let mut out = encoder.write(data)?;
+            out.extend_from_slice(&encoder.finish()?);
+
+            if out.len() >= data.len() {
+                content.body = body;
+                return Ok(content);
+            }
+
+            content.body = Body::ByteArray(out);
+            return compressed(content, coding);
+        }
+    };
+
+    let (tx, rx) = tokio::sync::mpsc::channel(4);
+    tokio::spawn(encode_body(source, encoder, tx));
+    content.body = Body::Channel(rx);
+
+    compressed(content, coding)
+}
+
+/// a strong ETag names the identity bytes, the compressed ones only match weakly
+fn compressed(mut content: ResponseContent, coding: &str) -> Result<ResponseContent> {
+    content.headers.set("Content-Encoding", coding.as_bytes())?;
+    content.headers.remove("Content-Length");
+    content.headers.remove("Accept-Ranges");
+
+    let etag = content.headers.get(EntityTag::name())
+        .and_then(|v| EntityTag::parse(v).ok());
+    if let Some(etag) = etag {
+        if !etag.weak {
+            content.headers.set_typed(&EntityTag::weak(&etag.tag))?;
+        }
+    }
+
+    Ok(content)
+}
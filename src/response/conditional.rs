@@ -0,0 +1,157 @@
+use crate::*;
+use crate::header::{TypedHeader, EntityTag, HttpDate, LastModified};
+use crate::header::{IfMatch, IfNoneMatch, IfModifiedSince, IfUnmodifiedSince};
+use crate::stream_handler::spool::SpoolBody;
+use super::StatusCode;
+
+/// FNV-1a over the bytes, with the length; a strong validator for a fixed body
+pub fn content_tag(data: &ByteSlice) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for c in data {
+        hash ^= *c as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+
+    format!("{:016x}-{:x}", hash, data.len())
+}
+
+/// a strong ETag for bodies already in memory
+pub fn body_etag(body: &Body) -> Option<EntityTag> {
+    let data: &ByteSlice = match body {
+        Body::ByteArray(v) => v,
+        Body::Utf8String(v) => v.as_bytes(),
+        Body::Static(v) => v,
+        Body::Shared(v) => v,
+        Body::Spool(SpoolBody::Memory(v)) => v,
+        _ => {
+            return None;
+        }
+    };
+
+    Some(EntityTag::strong(&content_tag(data)))
+}
+
+/// the ETag and Last-Modified the response carries
+pub(crate) fn validators(content: &ResponseContent) -> (Option<EntityTag>, Option<HttpDate>) {
+    let etag = content.headers.get(EntityTag::name())
+        .and_then(|v| EntityTag::parse(v).ok());
+    let last_modified = content.headers.get(LastModified::name())
+        .and_then(|v| LastModified::parse(v).ok())
+        .map(|l| l.0);
+
+    (etag, last_modified)
+}
+
+impl ResponseContent {
+    /// sets a strong ETag from the body unless one is set; false when the
+    /// body is not buffered
+    pub fn set_body_etag(&mut self) -> Result<bool> {
+        if self.headers.contains(EntityTag::name()) {
+            return Ok(true);
+        }
+
+        match body_etag(&self.body) {
+            Some(etag) => {
+                self.headers.set_typed(&etag)?;
+                Ok(true)
+            },
+            None => Ok(false)
+        }
+    }
+}
+
+/// a header that fails to parse is ignored, RFC 9110 13.1
+fn typed<H: TypedHeader + Clone>(request: &RequestHeader) -> Option<H> {
+    request.headers.typed::<H>().ok().and_then(|h| h)
+}
+
+fn without_body(mut content: ResponseContent, status: StatusCode) -> ResponseContent {
+    content.captial.set_status(status);
+
+    for name in &["Content-Type", "Content-Encoding", "Content-Range", "Accept-Ranges"] {
+        content.headers.remove(name);
+    }
+    content.body = Body::default();
+
+    content
+}
+
+/// the validators of the current representation of a resource, for `check`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Validators {
+    pub etag: Option<EntityTag>,
+    pub last_modified: Option<HttpDate>
+}
+
+/// evaluates the preconditions of RFC 9110 13.2.2 in order: If-Match, then
+/// If-Unmodified-Since, then If-None-Match, then If-Modified-Since; `current`
+/// is `None` when the resource has no representation yet.
+///
+/// `apply` runs on the response, after the handler acted, so a handler for
+/// PUT, DELETE or any other unsafe method calls this first and answers the
+/// returned 412 or 304 instead of performing the method
+pub fn check(request: &RequestHeader, current: Option<&Validators>) -> Option<StatusCode> {
+    let (etag, last_modified) = match current {
+        Some(v) => (v.etag.as_ref(), v.last_modified),
+        None => (None, None)
+    };
+    let safe = request.method == Method::Get || request.method == Method::Head;
+
+    if let Some(if_match) = typed::<IfMatch>(request) {
+        let matched = match (&if_match, etag) {
+            (IfMatch::Any, _) => current.is_some(),
+            (_, Some(e)) => if_match.matches(e),
+            (_, None) => false
+        };
+        if !matched {
+            return Some(StatusCode::PRECONDITION_FAILED);
+        }
+    } else if let Some(IfUnmodifiedSince(date)) = typed::<IfUnmodifiedSince>(request) {
+        if let Some(lm) = last_modified {
+            if lm > date {
+                return Some(StatusCode::PRECONDITION_FAILED);
+            }
+        }
+    }
+
+    if let Some(if_none_match) = typed::<IfNoneMatch>(request) {
+        let matched = match (&if_none_match, etag) {
+            (IfNoneMatch::Any, _) => current.is_some(),
+            (_, Some(e)) => if_none_match.matches(e),
+            (_, None) => false
+        };
+        if matched {
+            let status = if safe { StatusCode::NOT_MODIFIED } else { StatusCode::PRECONDITION_FAILED };
+            return Some(status);
+        }
+    } else if safe {
+        if let Some(IfModifiedSince(date)) = typed::<IfModifiedSince>(request) {
+            if let Some(lm) = last_modified {
+                if lm <= date {
+                    return Some(StatusCode::NOT_MODIFIED);
+                }
+            }
+        }
+    }
+
+    None
+}
+
+/// `check` against a 2xx response, turning it into 412 or 304; enough on
+/// its own for GET and HEAD, which change nothing
+pub fn apply(request: &RequestHeader, content: ResponseContent) -> Result<ResponseContent> {
+    if !content.captial.status().is_success() {
+        return Ok(content);
+    }
+
+    let (etag, last_modified) = validators(&content);
+    let current = Validators {
+        etag: etag,
+        last_modified: last_modified
+    };
+
+    match check(request, Some(&current)) {
+        Some(status) => Ok(without_body(content, status)),
+        None => Ok(content)
+    }
+}
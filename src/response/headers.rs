@@ -0,0 +1,149 @@
+use crate::*;
+use crate::header::{TypedHeader, is_token};
+
+// a second value replaces the first instead of being appended
+const SINGLE_VALUED: &[&str] = &[
+    "age", "content-length", "content-location", "content-range", "content-type",
+    "date", "etag", "expires", "last-modified", "location", "retry-after", "server"];
+
+fn is_single_valued(name: &str) -> bool {
+    SINGLE_VALUED.iter().any(|n| n.eq_ignore_ascii_case(name))
+}
+
+fn check_name(name: &str) -> Result<()> {
+    if !is_token(name) {
+        return Err(Error::Content(
+                format!("invalid response header name: {:?}", name)));
+    }
+
+    Ok(())
+}
+
+/// field-value is VCHAR, obs-text, SP and HTAB; any other control byte,
+/// CR and LF in particular, would split the response
+fn check_value(name: &str, value: &ByteSlice) -> Result<()> {
+    if let Some(i) = value.iter().position(|c| (*c < 0x20 && *c != b'\t') || *c == 0x7f) {
+        return Err(Error::Content(
+                format!("invalid byte 0x{:02x} at {} in response header {}", value[i], i, name)));
+    }
+
+    Ok(())
+}
+
+/// validated response header fields, serialized in insertion order
+#[derive(Default)]
+pub struct ResponseHeaders {
+    fields: Vec<(String, ByteArray)>,
+    unchecked: ResponseUncheckHeaders
+}
+
+impl ResponseHeaders {
+    /// replaces every field with the same name
+    pub fn set(&mut self, name: &str, value: &ByteSlice) -> Result<()> {
+        check_name(name)?;
+        check_value(name, value)?;
+
+        match self.fields.iter().position(|(n, _)| n.eq_ignore_ascii_case(name)) {
+            Some(i) => {
+                self.fields[i].1 = value.to_vec();
+                let mut j = i + 1;
+                while j < self.fields.len() {
+                    if self.fields[j].0.eq_ignore_ascii_case(name) {
+                        self.fields.remove(j);
+                    } else {
+                        j += 1;
+                    }
+                }
+            },
+            None => {
+                self.fields.push((name.to_string(), value.to_vec()));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// adds another field line, e.g. for Set-Cookie;
+    /// single-valued fields like Content-Type are replaced instead
+    pub fn append(&mut self, name: &str, value: &ByteSlice) -> Result<()> {
+        if is_single_valued(name) {
+            return self.set(name, value);
+        }
+
+        check_name(name)?;
+        check_value(name, value)?;
+
+        self.fields.push((name.to_string(), value.to_vec()));
+
+        Ok(())
+    }
+
+    pub fn set_typed<H: TypedHeader>(&mut self, h: &H) -> Result<()> {
+        self.set(H::name(), &h.to_bytearray())
+    }
+
+    pub fn append_typed<H: TypedHeader>(&mut self, h: &H) -> Result<()> {
+        self.append(H::name(), &h.to_bytearray())
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ByteSlice> {
+        self.fields.iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_slice())
+    }
+
+    pub fn get_all<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a ByteSlice> + 'a {
+        self.fields.iter()
+            .filter(move |(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_slice())
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    /// true when at least one field was removed
+    pub fn remove(&mut self, name: &str) -> bool {
+        let len = self.fields.len();
+        self.fields.retain(|(n, _)| !n.eq_ignore_ascii_case(name));
+        self.fields.len() != len
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &ByteSlice)> {
+        self.fields.iter().map(|(n, v)| (n.as_str(), v.as_slice()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.fields.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty() && self.unchecked.bytes.is_empty()
+    }
+
+    /// explicit opt-in to raw field lines that skip validation and dedup,
+    /// they are written after the checked fields
+    pub fn unchecked_mut(&mut self) -> &mut ResponseUncheckHeaders {
+        &mut self.unchecked
+    }
+
+    pub fn write(&self, buf: &mut ByteArray) {
+        let size: usize = self.fields.iter()
+            .map(|(n, v)| n.len() + v.len() + 4)
+            .sum();
+        buf.reserve(size + self.unchecked.bytes.len());
+
+        for (name, value) in &self.fields {
+            buf.extend_from_slice(name.as_bytes());
+            buf.extend_from_slice(b": ");
+            buf.extend_from_slice(value);
+            buf.extend_from_slice(b"\r\n");
+        }
+
+        buf.extend_from_slice(&self.unchecked.bytes);
+    }
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
@@ -0,0 +1,286 @@
+use crate::*;
+use tokio::io::AsyncWriteExt;
+use stream_handler::spool::SpoolBody;
+
+pub mod status;
+pub mod builder;
+pub mod headers;
+pub mod clock;
+pub mod sendfile;
+pub mod range;
+pub mod conditional;
+pub mod compress;
+
+pub use status::StatusCode;
+pub use builder::ResponseBuilder;
+pub use headers::ResponseHeaders;
+pub use sendfile::{FileBody, SendFile};
+pub use compress::CompressOptions;
+
+fn write_error(e: std::io::Error) -> Error {
+    Error::Content(format!("failed to write response: {:?}", e))
+}
+
+/// how the body is delimited on the wire
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResponseFraming {
+    /// 1xx, 204 and 304 never carry a body
+    NoBody,
+    ContentLength(u64),
+    Chunked,
+    /// HTTP/1.0 without a known length, the connection ends the body
+    Close
+}
+
+impl ResponseCaptial {
+    /// `HTTP/1.1 200 OK\r\n`
+    pub fn write(&self, buf: &mut ByteArray) {
+        buf.extend_from_slice(&self.version);
+        buf.push(b' ');
+        buf.extend_from_slice(self.status.as_u16().to_string().as_bytes());
+        buf.push(b' ');
+        buf.extend_from_slice(&self.status_desc);
+        buf.extend_from_slice(b"\r\n");
+    }
+}
+
+impl ResponseContent {
+    pub fn builder() -> ResponseBuilder {
+        ResponseBuilder::new()
+    }
+
+    pub fn framing(&self) -> Result<ResponseFraming> {
+        let status = self.captial.status();
+        if status.is_informational() || status == StatusCode::NO_CONTENT
+            || status == StatusCode::NOT_MODIFIED {
+            return Ok(ResponseFraming::NoBody);
+        }
+
+        let len = match &self.body {
+            Body::ByteArray(v) => Some(v.len() as u64),
+            Body::Utf8String(v) => Some(v.len() as u64),
+            Body::Static(v) => Some(v.len() as u64),
+            Body::Shared(v) => Some(v.len() as u64),
+            Body::Spool(v) => Some(v.len()),
+            Body::File(v) => Some(v.len()),
+            Body::Stream(v) => v.len().map(|n| (n - v.received()) as u64),
+            Body::Channel(_) => None,
+            Body::Form(_) | Body::Json(_) | Body::Structured(_, _) | Body::Custom(_) => {
+                return Err(Error::Content(
+                        "response body must be encoded to bytes before sending".to_string()));
+            }
+        };
+
+        match len {
+            Some(n) => Ok(ResponseFraming::ContentLength(n)),
+            None if self.captial.version == b"HTTP/1.1" => Ok(ResponseFraming::Chunked),
+            None => Ok(ResponseFraming::Close)
+        }
+    }
+
+    /// fills in Date and Server unless the handler set them, and always
+    /// replaces the framing headers with ones matching the body
+    pub fn prepare(&mut self, options: &ResponseOptions) -> Result<ResponseFraming> {
+        if options.date && !self.headers.contains("Date") {
+            self.headers.set("Date", &clock::date_bytes())?;
+        }
+
+        if let Some(server) = &options.server {
+            if !self.headers.contains("Server") {
+                self.headers.set("Server", server.as_bytes())?;
+            }
+        }
+
+        let framing = self.framing()?;
+
+        self.headers.remove("Transfer-Encoding");
+        self.headers.remove("Content-Length");
+
+        match framing {
+            ResponseFraming::NoBody => {},
+            ResponseFraming::ContentLength(n) => {
+                self.headers.set("Content-Length", n.to_string().as_bytes())?;
+            },
+            ResponseFraming::Chunked => {
+                self.headers.set("Transfer-Encoding", b"chunked")?;
+            },
+            ResponseFraming::Close => {
+                self.headers.set("Connection", b"close")?;
+            }
+        }
+
+        Ok(framing)
+    }
+
+    /// status line and header fields, up to and including the empty line
+    pub fn write_head(&self, buf: &mut ByteArray) {
+        self.captial.write(buf);
+        self.headers.write(buf);
+        buf.extend_from_slice(b"\r\n");
+    }
+}
+
+async fn write_chunk<W>(writer: &mut W, chunked: bool, data: &ByteSlice) -> Result<()>
+    where W: tokio::io::AsyncWrite + Unpin {
+    if data.is_empty() {
+        // a zero-size chunk would end the body
+        return Ok(());
+    }
+
+    if chunked {
+        writer.write_all(format!("{:x}\r\n", data.len()).as_bytes()).await.map_err(write_error)?;
+        writer.write_all(data).await.map_err(write_error)?;
+        writer.write_all(b"\r\n").await.map_err(write_error)?;
+    } else {
+        writer.write_all(data).await.map_err(write_error)?;
+    }
+
+    Ok(())
+}
+
+/// the body after the head, with the last chunk when `chunked`
+async fn write_body<W: SendFile>(writer: &mut W, body: Body, chunked: bool) -> Result<()> {
+    match body {
+        Body::ByteArray(v) => {
+            writer.write_all(&v).await.map_err(write_error)?;
+        },
+        Body::Utf8String(v) => {
+            writer.write_all(v.as_bytes()).await.map_err(write_error)?;
+        },
+        Body::Static(v) => {
+            writer.write_all(v).await.map_err(write_error)?;
+        },
+        Body::Shared(v) => {
+            writer.write_all(&v).await.map_err(write_error)?;
+        },
+        Body::Stream(mut stream) => {
+            while let Some(chunk) = stream.next_chunk().await? {
+                write_chunk(writer, chunked, &chunk).await?;
+            }
+        },
+        Body::Channel(mut rx) => {
+            while let Some(chunk) = rx.recv().await {
+                write_chunk(writer, chunked, &chunk?).await?;
+            }
+        },
+        Body::Spool(SpoolBody::Memory(v)) => {
+            writer.write_all(&v).await.map_err(write_error)?;
+        },
+        Body::Spool(SpoolBody::File(f)) => {
+            let file = FileBody::new(f.open().await?, 0, f.len());
+            sendfile::send_file(writer, file).await?;
+        },
+        Body::File(f) => {
+            sendfile::send_file(writer, f).await?;
+        },
+        Body::Form(_) | Body::Json(_) | Body::Structured(_, _) | Body::Custom(_) => {
+            unreachable!("rejected by framing");
+        }
+    }
+
+    if chunked {
+        writer.write_all(b"0\r\n\r\n").await.map_err(write_error)?;
+    }
+
+    Ok(())
+}
+
+/// ends a connection whose body failed part way; a plain TCP socket is
+/// reset so the client cannot take it for the end of the body
+async fn abort<W: SendFile>(writer: &mut W) {
+    if let Some(tcp) = writer.tcp_stream() {
+        if reset(tcp) {
+            return;
+        }
+    }
+
+    let _ = writer.shutdown().await;
+}
+
+/// closes the socket with a zero linger, which sends RST; waiting for the
+/// owner to drop it is not enough, an `OwnedWriteHalf` sends FIN on drop
+/// and the read half keeps the socket open. The fd is swapped for an
+/// unconnected socket so later calls and the final close on it are harmless
+#[cfg(target_os = "linux")]
+fn reset(tcp: &tokio::net::TcpStream) -> bool {
+    use std::os::unix::io::AsRawFd;
+
+    #[allow(deprecated)]
+    let linger = tcp.set_linger(Some(std::time::Duration::from_secs(0)));
+    if linger.is_err() {
+        return false;
+    }
+
+    let fd = tcp.as_raw_fd();
+
+    unsafe {
+        let placeholder = libc::socket(libc::AF_INET, libc::SOCK_STREAM | libc::SOCK_CLOEXEC, 0);
+        if placeholder < 0 {
+            return false;
+        }
+
+        // closes the connected socket in place of `fd`
+        let r = libc::dup3(placeholder, fd, libc::O_CLOEXEC);
+        libc::close(placeholder);
+
+        r >= 0
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn reset(_tcp: &tokio::net::TcpStream) -> bool {
+    false
+}
+
+impl<Writer: SendFile> Response<Writer> {
+    pub fn new(writer: std::sync::Arc<tokio::sync::RwLock<Writer>>, content: ResponseContent) -> Self {
+        Self {
+            writer: writer,
+            content: content,
+            options: std::sync::Arc::new(ResponseOptions::default()),
+            head_only: false
+        }
+    }
+
+    /// for HEAD requests, headers are framed for the full body but it is not sent
+    pub fn head_only(mut self, head_only: bool) -> Self {
+        self.head_only = head_only;
+        self
+    }
+
+    pub fn with_options(mut self, options: std::sync::Arc<ResponseOptions>) -> Self {
+        self.options = options;
+        self
+    }
+
+    pub fn content_ref(&self) -> &ResponseContent {
+        &self.content
+    }
+
+    pub fn content_mut(&mut self) -> &mut ResponseContent {
+        &mut self.content
+    }
+
+    pub async fn send(mut self) -> Result<()> {
+        let framing = self.content.prepare(&self.options)?;
+
+        let mut head = new_bytearray();
+        self.content.write_head(&mut head);
+
+        let mut writer = self.writer.write().await;
+        writer.write_all(&head).await.map_err(write_error)?;
+
+        let chunked = framing == ResponseFraming::Chunked;
+
+        if framing != ResponseFraming::NoBody && !self.head_only {
+            if let Err(e) = write_body(&mut *writer, self.content.body, chunked).await {
+                abort(&mut *writer).await;
+                return Err(e);
+            }
+        }
+
+        writer.flush().await.map_err(write_error)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,222 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+use crate::*;
+use crate::header::{TypedHeader, Range, ContentRange, IfRange};
+use crate::stream_handler::spool::SpoolBody;
+use super::{StatusCode, FileBody};
+use super::conditional::validators;
+
+/// more ranges than this are answered with the whole representation
+pub const MAX_RANGES: usize = 16;
+
+static NEXT_BOUNDARY: AtomicUsize = AtomicUsize::new(0);
+
+/// bodies that can be sliced, with their length
+fn body_len(body: &Body) -> Option<u64> {
+    match body {
+        Body::ByteArray(v) => Some(v.len() as u64),
+        Body::Utf8String(v) => Some(v.len() as u64),
+        Body::Static(v) => Some(v.len() as u64),
+        Body::Shared(v) => Some(v.len() as u64),
+        Body::File(f) => Some(f.len()),
+        Body::Spool(SpoolBody::Memory(v)) => Some(v.len() as u64),
+        _ => None
+    }
+}
+
+fn slice_bytes(v: &ByteSlice, first: u64, last: u64) -> ByteArray {
+    v[first as usize..last as usize + 1].to_vec()
+}
+
+/// the inclusive `first..=last` of an in-memory body
+fn memory_slice(body: &Body, first: u64, last: u64) -> Option<ByteArray> {
+    match body {
+        Body::ByteArray(v) => Some(slice_bytes(v, first, last)),
+        Body::Utf8String(v) => Some(slice_bytes(v.as_bytes(), first, last)),
+        Body::Static(v) => Some(slice_bytes(v, first, last)),
+        Body::Shared(v) => Some(slice_bytes(v, first, last)),
+        Body::Spool(SpoolBody::Memory(v)) => Some(slice_bytes(v, first, last)),
+        _ => None
+    }
+}
+
+fn boundary() -> String {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or(0);
+
+    format!("{:08x}{:08x}{:04x}", std::process::id(), nanos
+            , NEXT_BOUNDARY.fetch_add(1, Ordering::Relaxed) & 0xffff)
+}
+
+fn part_head(boundary: &str, content_type: Option<&ByteSlice>, first: u64, last: u64, len: u64) -> ByteArray {
+    let mut head = new_bytearray();
+    head.extend_from_slice(b"\r\n--");
+    head.extend_from_slice(boundary.as_bytes());
+    head.extend_from_slice(b"\r\n");
+    if let Some(ct) = content_type {
+        head.extend_from_slice(b"Content-Type: ");
+        head.extend_from_slice(ct);
+        head.extend_from_slice(b"\r\n");
+    }
+    head.extend_from_slice(b"Content-Range: ");
+    ContentRange::Bytes { first: first, last: last, complete: Some(len) }.write(&mut head);
+    head.extend_from_slice(b"\r\n\r\n");
+    head
+}
+
+fn part_error(desc: String) -> Error {
+    Error::Content(format!("failed to read range: {}", desc))
+}
+
+/// streams the parts of a file-backed multipart/byteranges body; a read
+/// error, or the file ending early, is sent in place of the rest
+async fn send_file_parts(file: FileBody, parts: Vec<(ByteArray, u64, u64)>, trailer: ByteArray
+                         , tx: tokio::sync::mpsc::Sender<Result<ByteArray>>) {
+    let (mut file, offset, _) = file.into_parts();
+
+    for (head, first, last) in parts {
+        if tx.send(Ok(head)).await.is_err() {
+            return;
+        }
+
+        if let Err(e) = file.seek(std::io::SeekFrom::Start(offset + first)).await {
+            let _ = tx.send(Err(part_error(format!("{:?}", e)))).await;
+            return;
+        }
+
+        let mut remaining = last - first + 1;
+        while remaining > 0 {
+            let mut buf = vec![0u8; std::cmp::min(remaining, 64 * 1024) as usize];
+            match file.read(&mut buf).await {
+                Ok(0) => {
+                    let _ = tx.send(Err(part_error(format!("file ended early")))).await;
+                    return;
+                },
+                Err(e) => {
+                    let _ = tx.send(Err(part_error(format!("{:?}", e)))).await;
+                    return;
+                },
+                Ok(n) => {
+                    buf.truncate(n);
+                    remaining -= n as u64;
+                    if tx.send(Ok(buf)).await.is_err() {
+                        return;
+                    }
+                }
+            }
+        }
+    }
+
+    let _ = tx.send(Ok(trailer)).await;
+}
+
+/// answers `Range` on a 200 GET response: 206 with one range or
+/// multipart/byteranges, or 416 when nothing is satisfiable; an invalid
+/// `Range`, a failed `If-Range` or too many ranges leave the full 200
+pub fn apply(request: &RequestHeader, mut content: ResponseContent) -> Result<ResponseContent> {
+    if content.captial.status() != StatusCode::OK {
+        return Ok(content);
+    }
+
+    let len = match body_len(&content.body) {
+        Some(l) => l,
+        None => {
+            return Ok(content);
+        }
+    };
+
+    content.headers.set("Accept-Ranges", b"bytes")?;
+
+    if request.method != Method::Get {
+        return Ok(content);
+    }
+
+    let range = match request.headers.typed::<Range>() {
+        Ok(Some(r)) => r,
+        _ => {
+            return Ok(content);
+        }
+    };
+
+    if range.specs().len() > MAX_RANGES {
+        return Ok(content);
+    }
+
+    if let Ok(Some(if_range)) = request.headers.typed::<IfRange>() {
+        let (etag, last_modified) = validators(&content);
+        if !if_range.matches(etag.as_ref(), last_modified) {
+            return Ok(content);
+        }
+    }
+
+    let mut ranges: Vec<(u64, u64)> = range.specs().iter()
+        .filter_map(|s| s.to_satisfiable(len))
+        .collect();
+
+    if ranges.is_empty() {
+        content.captial.set_status(StatusCode::RANGE_NOT_SATISFIABLE);
+        content.headers.remove("Content-Type");
+        content.headers.set_typed(&ContentRange::Unsatisfied(len))?;
+        content.body = Body::default();
+        return Ok(content);
+    }
+
+    content.captial.set_status(StatusCode::PARTIAL_CONTENT);
+
+    if ranges.len() == 1 {
+        let (first, last) = ranges[0];
+        content.headers.set_typed(&ContentRange::Bytes { first: first, last: last, complete: Some(len) })?;
+
+        content.body = match content.body {
+            Body::File(f) => Body::File(f.slice(first, last - first + 1)),
+            Body::Static(v) => Body::Static(&v[first as usize..last as usize + 1]),
+            body => {
+                match memory_slice(&body, first, last) {
+                    Some(v) => Body::ByteArray(v),
+                    None => body
+                }
+            }
+        };
+
+        return Ok(content);
+    }
+
+    ranges.sort();
+
+    let boundary = boundary();
+    let content_type = content.headers.get("Content-Type").map(|v| v.to_vec());
+    let content_type = content_type.as_ref().map(|v| v.as_slice());
+
+    let parts: Vec<(ByteArray, u64, u64)> = ranges.iter()
+        .map(|(first, last)| {
+            (part_head(&boundary, content_type, *first, *last, len), *first, *last)
+        })
+        .collect();
+    let trailer = format!("\r\n--{}--\r\n", boundary).into_bytes();
+
+    content.headers.set("Content-Type", format!("multipart/byteranges; boundary={}", boundary).as_bytes())?;
+
+    content.body = match content.body {
+        Body::File(f) => {
+            let (tx, rx) = tokio::sync::mpsc::channel(4);
+            tokio::spawn(send_file_parts(f, parts, trailer, tx));
+            Body::Channel(rx)
+        },
+        body => {
+            let mut out = new_bytearray();
+            for (head, first, last) in parts {
+                out.extend_from_slice(&head);
+                if let Some(v) = memory_slice(&body, first, last) {
+                    out.extend_from_slice(&v);
+                }
+            }
+            out.extend_from_slice(&trailer);
+            Body::ByteArray(out)
+        }
+    };
+
+    Ok(content)
+}
@@ -0,0 +1,184 @@
+use tokio::io::{AsyncSeekExt, AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::net::tcp::OwnedWriteHalf;
+
+use crate::*;
+use super::write_error;
+
+/// a byte range of an open file, sent with `sendfile` where the writer allows it
+pub struct FileBody {
+    file: tokio::fs::File,
+    offset: u64,
+    len: u64
+}
+
+impl FileBody {
+    pub fn new(file: tokio::fs::File, offset: u64, len: u64) -> Self {
+        Self {
+            file: file,
+            offset: offset,
+            len: len
+        }
+    }
+
+    /// the whole file
+    pub async fn open<P: AsRef<std::path::Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+
+        let file = match tokio::fs::File::open(path).await {
+            Ok(f) => f,
+            Err(e) => {
+                return Err(Error::Content(
+                        format!("failed to open {:?}: {:?}", path, e)));
+            }
+        };
+
+        let len = match file.metadata().await {
+            Ok(m) => m.len(),
+            Err(e) => {
+                return Err(Error::Content(
+                        format!("failed to stat {:?}: {:?}", path, e)));
+            }
+        };
+
+        Ok(Self::new(file, 0, len))
+    }
+
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// `len` bytes from `offset` within this range, clamped to it
+    pub fn slice(self, offset: u64, len: u64) -> Self {
+        let offset = std::cmp::min(offset, self.len);
+        let len = std::cmp::min(len, self.len - offset);
+
+        Self {
+            file: self.file,
+            offset: self.offset + offset,
+            len: len
+        }
+    }
+
+    /// (file, offset, len)
+    pub fn into_parts(self) -> (tokio::fs::File, u64, u64) {
+        (self.file, self.offset, self.len)
+    }
+}
+
+/// response writers; plain TCP sockets expose themselves for zero-copy
+/// `sendfile`, wrapped streams like TLS keep the default and get a buffered copy
+pub trait SendFile: tokio::io::AsyncWrite + Send + Unpin {
+    fn tcp_stream(&self) -> Option<&TcpStream> {
+        None
+    }
+}
+
+impl SendFile for TcpStream {
+    fn tcp_stream(&self) -> Option<&TcpStream> {
+        Some(self)
+    }
+}
+
+impl SendFile for OwnedWriteHalf {
+    fn tcp_stream(&self) -> Option<&TcpStream> {
+        Some(self.as_ref())
+    }
+}
+
+impl SendFile for tokio::io::DuplexStream {}
+
+impl SendFile for Vec<u8> {}
+
+impl<T: tokio::io::AsyncRead + tokio::io::AsyncWrite + Send + Unpin> SendFile for tokio::io::WriteHalf<T> {}
+
+fn truncated() -> Error {
+    Error::Content("file ended before the response body was complete".to_string())
+}
+
+async fn copy_buffered<W: SendFile>(writer: &mut W, body: &mut FileBody) -> Result<()> {
+    body.file.seek(std::io::SeekFrom::Start(body.offset)).await.map_err(write_error)?;
+
+    let mut buf = vec![0u8; 64 * 1024];
+    let mut remaining = body.len;
+
+    while remaining > 0 {
+        let size = std::cmp::min(remaining, buf.len() as u64) as usize;
+        let n = body.file.read(&mut buf[..size]).await.map_err(write_error)?;
+        if n == 0 {
+            return Err(truncated());
+        }
+
+        writer.write_all(&buf[..n]).await.map_err(write_error)?;
+        remaining -= n as u64;
+    }
+
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+async fn copy_sendfile(socket: &TcpStream, body: &FileBody) -> Result<()> {
+    use std::os::unix::io::AsRawFd;
+
+    // the largest count a single sendfile call transfers
+    const MAX_COUNT: u64 = 0x7fff_f000;
+
+    let out_fd = socket.as_raw_fd();
+    let in_fd = body.file.as_raw_fd();
+    let mut offset = body.offset as libc::off_t;
+    let mut remaining = body.len;
+
+    while remaining > 0 {
+        socket.writable().await.map_err(write_error)?;
+
+        let count = std::cmp::min(remaining, MAX_COUNT) as usize;
+        let r = socket.try_io(tokio::io::Interest::WRITABLE, || {
+            let n = unsafe { libc::sendfile(out_fd, in_fd, &mut offset, count) };
+            if n < 0 {
+                Err(std::io::Error::last_os_error())
+            } else {
+                Ok(n as u64)
+            }
+        });
+
+        match r {
+            Ok(0) => {
+                return Err(truncated());
+            },
+            Ok(n) => {
+                remaining -= n;
+            },
+            Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                continue;
+            },
+            Err(e) => {
+                return Err(write_error(e));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// writes `body` after whatever the writer already holds
+pub async fn send_file<W: SendFile>(writer: &mut W, mut body: FileBody) -> Result<()> {
+    #[cfg(target_os = "linux")]
+    {
+        if writer.tcp_stream().is_some() {
+            writer.flush().await.map_err(write_error)?;
+            if let Some(socket) = writer.tcp_stream() {
+                return copy_sendfile(socket, &body).await;
+            }
+        }
+    }
+
+    copy_buffered(writer, &mut body).await
+}
@@ -0,0 +1,233 @@
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::*;
+
+/// what the handler needs from a stat
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileMeta {
+    pub is_dir: bool,
+    pub is_file: bool,
+    pub len: u64,
+    pub modified: Option<SystemTime>
+}
+
+impl From<&std::fs::Metadata> for FileMeta {
+    fn from(m: &std::fs::Metadata) -> Self {
+        Self {
+            is_dir: m.is_dir(),
+            is_file: m.is_file(),
+            len: m.len(),
+            modified: m.modified().ok()
+        }
+    }
+}
+
+pub(crate) async fn stat(p: &Path) -> Result<Option<FileMeta>> {
+    match tokio::fs::metadata(p).await {
+        Ok(m) => Ok(Some(FileMeta::from(&m))),
+        Err(ref e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(super::fs_error("stat", p, e))
+    }
+}
+
+pub struct CacheOptions {
+    /// stat results, missing files included
+    pub max_entries: usize,
+    /// file contents across all entries
+    pub max_bytes: usize,
+    /// larger files are always sent from disk
+    pub max_file_size: u64,
+    /// how long a stat result is trusted before the file is checked again
+    pub revalidate: Duration
+}
+
+impl Default for CacheOptions {
+    fn default() -> Self {
+        Self {
+            max_entries: 1024,
+            max_bytes: 32 * 1024 * 1024,
+            max_file_size: 256 * 1024,
+            revalidate: Duration::from_secs(1)
+        }
+    }
+}
+
+struct Entry {
+    meta: Option<FileMeta>,
+    data: Option<Arc<ByteSlice>>,
+    checked: Instant,
+    used: u64
+}
+
+/// `order` holds a key each time it is used, oldest first; only the item
+/// whose generation matches `Entry::used` is live, the others are skipped
+#[derive(Default)]
+struct Inner {
+    entries: HashMap<PathBuf, Entry>,
+    order: VecDeque<(PathBuf, u64)>,
+    bytes: usize,
+    tick: u64
+}
+
+impl Inner {
+    /// marks `p` most recently used
+    fn touch(&mut self, p: &Path) -> Option<&mut Entry> {
+        self.tick += 1;
+        self.entries.get_mut(p)?.used = self.tick;
+        self.order.push_back((p.to_path_buf(), self.tick));
+
+        // stale items are dropped once they outnumber the live ones
+        if self.order.len() > 2 * self.entries.len() + 16 {
+            let entries = &self.entries;
+            self.order.retain(|(k, used)| entries.get(k).map(|e| e.used) == Some(*used));
+        }
+
+        self.entries.get_mut(p)
+    }
+
+    fn remove(&mut self, p: &Path) {
+        if let Some(e) = self.entries.remove(p) {
+            if let Some(d) = e.data {
+                self.bytes -= d.len();
+            }
+        }
+    }
+
+    /// drops least recently used entries until both limits hold
+    fn evict(&mut self, options: &CacheOptions) {
+        while self.entries.len() > options.max_entries || self.bytes > options.max_bytes {
+            let (p, used) = match self.order.pop_front() {
+                Some(item) => item,
+                None => break
+            };
+
+            let live = match self.entries.get(&p) {
+                Some(e) => e.used == used,
+                None => false
+            };
+            if live {
+                self.remove(&p);
+            }
+        }
+    }
+}
+
+/// bounded LRU of stat results and small file contents, keyed by path;
+/// contents are dropped once a revalidating stat sees another mtime or size
+pub struct AssetCache {
+    options: CacheOptions,
+    inner: Mutex<Inner>
+}
+
+impl AssetCache {
+    pub fn new(options: CacheOptions) -> Self {
+        Self {
+            options: options,
+            inner: Mutex::new(Inner::default())
+        }
+    }
+
+    /// `stat`, from memory within the revalidate interval
+    pub async fn stat(&self, p: &Path) -> Result<Option<FileMeta>> {
+        if let Ok(mut inner) = self.inner.lock() {
+            if let Some(e) = inner.touch(p) {
+                if e.checked.elapsed() < self.options.revalidate {
+                    return Ok(e.meta.clone());
+                }
+            }
+        }
+
+        let meta = stat(p).await?;
+
+        if let Ok(mut inner) = self.inner.lock() {
+            let changed = match inner.entries.get(p) {
+                Some(e) => e.meta != meta,
+                None => false
+            };
+            if changed {
+                inner.remove(p);
+            }
+
+            inner.entries.entry(p.to_path_buf()).or_insert_with(|| Entry {
+                meta: None,
+                data: None,
+                checked: Instant::now(),
+                used: 0
+            });
+            if let Some(e) = inner.touch(p) {
+                e.meta = meta.clone();
+                e.checked = Instant::now();
+            }
+
+            inner.evict(&self.options);
+        }
+
+        Ok(meta)
+    }
+
+    /// the contents of a file `stat` just returned `meta` for,
+    /// `None` when it is too large to cache
+    pub async fn read(&self, p: &Path, meta: &FileMeta) -> Result<Option<Arc<ByteSlice>>> {
+        if !meta.is_file || meta.len > self.options.max_file_size {
+            return Ok(None);
+        }
+
+        if let Ok(mut inner) = self.inner.lock() {
+            if let Some(e) = inner.touch(p) {
+                if e.meta.as_ref() == Some(meta) {
+                    if let Some(d) = &e.data {
+                        return Ok(Some(d.clone()));
+                    }
+                }
+            }
+        }
+
+        let data = match tokio::fs::read(p).await {
+            Ok(d) => d,
+            Err(e) => {
+                return Err(super::fs_error("read", p, e));
+            }
+        };
+
+        // changed between the stat and the read
+        if data.len() as u64 != meta.len {
+            return Ok(None);
+        }
+
+        let data: Arc<ByteSlice> = Arc::from(data);
+
+        if let Ok(mut inner) = self.inner.lock() {
+            let mut added = 0;
+            if let Some(e) = inner.entries.get_mut(p) {
+                if e.meta.as_ref() == Some(meta) && e.data.is_none() {
+                    e.data = Some(data.clone());
+                    added = data.len();
+                }
+            }
+            inner.bytes += added;
+
+            inner.evict(&self.options);
+        }
+
+        Ok(Some(data))
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.lock().map(|i| i.entries.len()).unwrap_or(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn clear(&self) {
+        if let Ok(mut inner) = self.inner.lock() {
+            inner.entries.clear();
+            inner.order.clear();
+            inner.bytes = 0;
+        }
+    }
+}
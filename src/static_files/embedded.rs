@@ -0,0 +1,259 @@
+use std::path::Path;
+
+use crate::*;
+use crate::header::EntityTag;
+use crate::response::{ResponseBuilder, StatusCode, range, conditional};
+use crate::response::conditional::content_tag;
+use crate::stream_handler::path;
+use super::{StaticOptions, StaticHandler, ServeFuture, mime};
+use super::{not_found, reject_method, request_path, choose_encoding};
+
+/// a precompressed form of an embedded file
+pub struct EmbeddedVariant {
+    pub coding: &'static str,
+    pub data: &'static ByteSlice,
+    /// the entity-tag without quotes
+    pub etag: &'static str
+}
+
+/// one asset compiled into the binary, usually generated by `generate`
+pub struct EmbeddedFile {
+    /// normalized request path, e.g. `/css/site.css`
+    pub path: &'static str,
+    pub data: &'static ByteSlice,
+    pub etag: &'static str,
+    pub mime: &'static str,
+    pub variants: &'static [EmbeddedVariant]
+}
+
+/// embeds the output of `generate`, written to `$OUT_DIR/$file` by a build script:
+/// `static ASSETS: &[EmbeddedFile] = include_assets!("assets.rs");`
+#[macro_export]
+macro_rules! include_assets {
+    ($file:expr) => {
+        include!(concat!(env!("OUT_DIR"), "/", $file))
+    };
+}
+
+fn walk(root: &Path, dir: &Path, files: &mut Vec<(String, std::path::PathBuf)>) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let p = entry.path();
+
+        if entry.file_type()?.is_dir() {
+            walk(root, &p, files)?;
+            continue;
+        }
+
+        let rel = match p.strip_prefix(root).ok().and_then(|r| r.to_str()) {
+            Some(r) => r.replace('\\', "/"),
+            None => {
+                return Err(std::io::Error::new(std::io::ErrorKind::InvalidData
+                        , format!("asset path is not valid utf-8: {:?}", p)));
+            }
+        };
+
+        files.push((format!("/{}", rel), p));
+    }
+
+    Ok(())
+}
+
+/// Rust source for an `&[EmbeddedFile]` over every file under `dir`, meant for
+/// build scripts; `x.br` and `x.gz` next to `x` become its variants
+pub fn generate(dir: &Path) -> std::io::Result<String> {
+    let root = std::fs::canonicalize(dir)?;
+
+    let mut files = Vec::new();
+    walk(&root, &root, &mut files)?;
+    files.sort();
+
+    let is_variant = |path: &str| -> bool {
+        super::PRECOMPRESSED.iter().any(|(_, ext)| {
+            let suffix = format!(".{}", ext);
+            path.ends_with(&suffix)
+                && files.iter().any(|(p, _)| *p == path[..path.len() - suffix.len()])
+        })
+    };
+
+    let mut out = String::from("&[\n");
+
+    for (path, fs_path) in &files {
+        if is_variant(path) {
+            continue;
+        }
+
+        let data = std::fs::read(fs_path)?;
+
+        out.push_str("    ::tokio_httpserver::static_files::EmbeddedFile {\n");
+        out.push_str(&format!("        path: {:?},\n", path));
+        out.push_str(&format!("        data: include_bytes!({:?}),\n", fs_path));
+        out.push_str(&format!("        etag: {:?},\n", content_tag(&data)));
+        out.push_str(&format!("        mime: {:?},\n", mime::guess(fs_path)));
+        out.push_str("        variants: &[\n");
+
+        for (coding, ext) in super::PRECOMPRESSED {
+            let variant = format!("{}.{}", path, ext);
+            if let Some((_, p)) = files.iter().find(|(v, _)| *v == variant) {
+                let data = std::fs::read(p)?;
+                out.push_str("            ::tokio_httpserver::static_files::EmbeddedVariant {\n");
+                out.push_str(&format!("                coding: {:?},\n", coding));
+                out.push_str(&format!("                data: include_bytes!({:?}),\n", p));
+                out.push_str(&format!("                etag: {:?}\n", content_tag(&data)));
+                out.push_str("            },\n");
+            }
+        }
+
+        out.push_str("        ]\n    },\n");
+    }
+
+    out.push_str("]\n");
+
+    Ok(out)
+}
+
+/// `generate` into `out`, with `cargo:rerun-if-changed` for `dir`
+pub fn write(dir: &Path, out: &Path) -> std::io::Result<()> {
+    println!("cargo:rerun-if-changed={}", dir.display());
+
+    std::fs::write(out, generate(dir)?)
+}
+
+/// serves `files` like `StaticFiles` serves a directory; `listing` and
+/// `cache` of the options do not apply
+pub struct EmbeddedAssets {
+    files: Vec<&'static EmbeddedFile>,
+    options: StaticOptions
+}
+
+impl EmbeddedAssets {
+    pub fn new(files: &'static [EmbeddedFile], options: StaticOptions) -> Self {
+        let mut files: Vec<&'static EmbeddedFile> = files.iter().collect();
+        files.sort_by_key(|f| f.path);
+
+        Self {
+            files: files,
+            options: options
+        }
+    }
+
+    pub fn get(&self, path: &str) -> Option<&'static EmbeddedFile> {
+        match self.files.binary_search_by_key(&path, |f| f.path) {
+            Ok(i) => Some(self.files[i]),
+            Err(_) => None
+        }
+    }
+
+    fn is_dir(&self, path: &str) -> bool {
+        let prefix = format!("{}/", path);
+        let i = match self.files.binary_search_by_key(&prefix.as_str(), |f| f.path) {
+            Ok(i) => i,
+            Err(i) => i
+        };
+
+        self.files.get(i).map(|f| f.path.starts_with(&prefix)).unwrap_or(false)
+    }
+
+    fn index(&self, dir: &str) -> Option<&'static EmbeddedFile> {
+        self.options.index.as_ref().and_then(|i| self.get(&format!("{}{}", dir, i)))
+    }
+
+    pub async fn serve(&self, request: &RequestHeader, target: &ByteSlice) -> Result<ResponseContent> {
+        let content = self.resolve(request, target)?;
+        let content = conditional::apply(request, content)?;
+
+        range::apply(request, content)
+    }
+
+    fn resolve(&self, request: &RequestHeader, target: &ByteSlice) -> Result<ResponseContent> {
+        if let Some(r) = reject_method(request) {
+            return r;
+        }
+
+        let path = request_path(target, &self.options)?;
+
+        if path.ends_with('/') {
+            if let Some(f) = self.index(&path) {
+                return self.file(request, f);
+            }
+            if self.is_dir(path.trim_end_matches('/')) {
+                return Err(Error::Detail(ErrorKind::Forbidden, format!("no index for {}", path)));
+            }
+        } else {
+            if let Some(f) = self.get(&path) {
+                return self.file(request, f);
+            }
+            if let Some(v) = self.variant(&path) {
+                return self.variant_file(&path, v);
+            }
+            if self.is_dir(&path) {
+                return ResponseBuilder::new()
+                    .status(StatusCode::MOVED_PERMANENTLY)
+                    .header("Location", &format!("{}/", path::encode(&path)))
+                    .build();
+            }
+        }
+
+        if self.options.spa_fallback {
+            let index = self.options.index.as_ref().map(|i| i.as_str()).unwrap_or("index.html");
+            if let Some(f) = self.get(&format!("/{}", index)) {
+                return self.file(request, f);
+            }
+        }
+
+        Err(not_found(&path))
+    }
+
+    /// a variant requested by its own name, e.g. `/app.js.gz`, which
+    /// `generate` folds into `/app.js`
+    fn variant(&self, path: &str) -> Option<&'static EmbeddedVariant> {
+        super::PRECOMPRESSED.iter().find_map(|(coding, ext)| {
+            let base = path.strip_suffix(ext)?.strip_suffix('.')?;
+            self.get(base)?.variants.iter().find(|v| v.coding == *coding)
+        })
+    }
+
+    /// as is, like `StaticFiles` serves the sibling file
+    fn variant_file(&self, path: &str, v: &'static EmbeddedVariant) -> Result<ResponseContent> {
+        ResponseBuilder::new()
+            .header("Content-Type", mime::guess(Path::new(path)))
+            .typed_header(&EntityTag::strong(v.etag))
+            .body(Body::Static(v.data))
+            .build()
+    }
+
+    fn file(&self, request: &RequestHeader, f: &'static EmbeddedFile) -> Result<ResponseContent> {
+        let variant = if self.options.precompressed {
+            let available: Vec<&'static str> = f.variants.iter().map(|v| v.coding).collect();
+            let chosen = choose_encoding(request, &available);
+            f.variants.iter().find(|v| v.coding == chosen)
+        } else {
+            None
+        };
+
+        let (data, etag) = match variant {
+            Some(v) => (v.data, v.etag),
+            None => (f.data, f.etag)
+        };
+
+        let mut builder = ResponseBuilder::new()
+            .header("Content-Type", f.mime)
+            .typed_header(&EntityTag::strong(etag));
+
+        if let Some(v) = variant {
+            builder = builder.header("Content-Encoding", v.coding);
+        }
+
+        if self.options.precompressed && !f.variants.is_empty() {
+            builder = builder.append_header("Vary", "Accept-Encoding");
+        }
+
+        builder.body(Body::Static(data)).build()
+    }
+}
+
+impl StaticHandler for EmbeddedAssets {
+    fn serve<'a>(&'a self, request: &'a RequestHeader, target: &'a ByteSlice) -> ServeFuture<'a> {
+        Box::pin(EmbeddedAssets::serve(self, request, target))
+    }
+}
@@ -0,0 +1,82 @@
+use crate::*;
+use crate::stream_handler::path::encode_segment;
+
+fn escape_html(s: &str, out: &mut String) {
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c)
+        }
+    }
+}
+
+/// an html index of `dir`, `path` is the normalized request path ending in `/`;
+/// directories come first, hidden entries are left out unless `hidden`
+pub async fn render(dir: &std::path::Path, path: &str, hidden: bool) -> Result<String> {
+    let mut read = match tokio::fs::read_dir(dir).await {
+        Ok(r) => r,
+        Err(e) => {
+            return Err(super::fs_error("list", dir, e));
+        }
+    };
+
+    let mut entries: Vec<(bool, String)> = Vec::new();
+    loop {
+        let entry = match read.next_entry().await {
+            Ok(Some(e)) => e,
+            Ok(None) => break,
+            Err(e) => {
+                return Err(super::fs_error("list", dir, e));
+            }
+        };
+
+        let name = match entry.file_name().into_string() {
+            Ok(n) => n,
+            Err(_) => continue
+        };
+
+        if !hidden && name.starts_with('.') {
+            continue;
+        }
+
+        let is_dir = match entry.file_type().await {
+            Ok(t) => t.is_dir(),
+            Err(_) => continue
+        };
+
+        entries.push((is_dir, name));
+    }
+
+    entries.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+
+    let mut html = String::from("<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>Index of ");
+    escape_html(path, &mut html);
+    html.push_str("</title></head>\n<body><h1>Index of ");
+    escape_html(path, &mut html);
+    html.push_str("</h1>\n<ul>\n");
+
+    if path != "/" {
+        html.push_str("<li><a href=\"../\">../</a></li>\n");
+    }
+
+    for (is_dir, name) in entries {
+        let suffix = if is_dir { "/" } else { "" };
+
+        // `./` keeps a name with a colon from reading as a scheme
+        html.push_str("<li><a href=\"./");
+        html.push_str(&encode_segment(&name));
+        html.push_str(suffix);
+        html.push_str("\">");
+        escape_html(&name, &mut html);
+        html.push_str(suffix);
+        html.push_str("</a></li>\n");
+    }
+
+    html.push_str("</ul></body></html>\n");
+
+    Ok(html)
+}
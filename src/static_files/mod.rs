@@ -0,0 +1,335 @@
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::RwLock;
+use std::time::UNIX_EPOCH;
+
+use crate::*;
+use crate::header::{EntityTag, HttpDate, LastModified, negotiate};
+use crate::response::{ResponseBuilder, StatusCode, FileBody, range, conditional};
+use crate::stream_handler::path;
+
+pub mod mime;
+pub mod listing;
+pub mod cache;
+pub mod embedded;
+
+pub use cache::{AssetCache, CacheOptions, FileMeta};
+pub use embedded::{EmbeddedAssets, EmbeddedFile, EmbeddedVariant};
+
+pub struct StaticOptions {
+    /// served for a directory request, e.g. `index.html`
+    pub index: Option<String>,
+    /// an html index for directories without an index file, otherwise 403;
+    /// embedded assets are never listed
+    pub listing: bool,
+    /// missing paths get the root index file, for client-side routed apps
+    pub spa_fallback: bool,
+    /// serve dot files and list them
+    pub hidden: bool,
+    /// prefer `file.br` or `file.gz` next to `file` when Accept-Encoding allows
+    pub precompressed: bool,
+    /// keep stat results and small files in memory
+    pub cache: Option<CacheOptions>
+}
+
+impl Default for StaticOptions {
+    fn default() -> Self {
+        Self {
+            index: Some("index.html".to_string()),
+            listing: false,
+            spa_fallback: false,
+            hidden: false,
+            precompressed: true,
+            cache: None
+        }
+    }
+}
+
+// preferred first when the client weighs them equally
+const PRECOMPRESSED: &[(&str, &str)] = &[("br", "br"), ("gzip", "gz")];
+
+/// serves GET and HEAD for a directory tree
+pub struct StaticFiles {
+    root: PathBuf,
+    options: StaticOptions,
+    cache: Option<AssetCache>
+}
+
+fn not_found(path: &str) -> Error {
+    Error::Detail(ErrorKind::NotMatched, format!("no file for {}", path))
+}
+
+/// 405 for anything but GET and HEAD
+fn reject_method(request: &RequestHeader) -> Option<Result<ResponseContent>> {
+    match request.method {
+        Method::Get | Method::Head => None,
+        _ => {
+            Some(ResponseBuilder::new()
+                .status(StatusCode::METHOD_NOT_ALLOWED)
+                .header("Allow", "GET, HEAD")
+                .build())
+        }
+    }
+}
+
+/// the normalized path, dot files are 404 unless `hidden`
+fn request_path(target: &ByteSlice, options: &StaticOptions) -> Result<String> {
+    let path = path::normalize(target)?;
+
+    if !options.hidden && path::segments(&path).any(|s| s.starts_with('.')) {
+        return Err(not_found(&path));
+    }
+
+    Ok(path)
+}
+
+/// one of the `available` codings or `identity`; a client without
+/// Accept-Encoding gets identity rather than the first offer, and so does
+/// one that refuses everything, like without variants
+fn choose_encoding(request: &RequestHeader, available: &[&'static str]) -> &'static str {
+    if available.is_empty() || request.headers.get(b"Accept-Encoding").is_none() {
+        return "identity";
+    }
+
+    let mut offered = available.to_vec();
+    offered.push("identity");
+
+    match negotiate::encoding(&request.headers, &offered) {
+        Ok(c) => c,
+        Err(_) => "identity"
+    }
+}
+
+/// boxed so handlers over different sources can sit behind one `dyn StaticHandler`
+pub type ServeFuture<'a> = Pin<Box<dyn Future<Output = Result<ResponseContent>> + Send + 'a>>;
+
+/// GET and HEAD over a tree of assets, on disk or compiled in
+pub trait StaticHandler: Send + Sync {
+    /// `target` is the raw request target; send the result with
+    /// `Response::head_only` for HEAD
+    fn serve<'a>(&'a self, request: &'a RequestHeader, target: &'a ByteSlice) -> ServeFuture<'a>;
+}
+
+/// sees the action, path and cause of every file system error, which the
+/// returned `Error` leaves out since its message can end up in a response
+pub type ErrorHook = fn(&str, &Path, &std::io::Error);
+
+static ERROR_HOOK: RwLock<Option<ErrorHook>> = RwLock::new(None);
+
+/// e.g. to log file system errors, without a hook they are only returned
+pub fn set_error_hook(hook: Option<ErrorHook>) {
+    match ERROR_HOOK.write() {
+        Ok(mut h) => *h = hook,
+        Err(e) => *e.into_inner() = hook
+    }
+}
+
+/// reports a file system error to the hook and returns one without its path
+fn fs_error(action: &str, p: &Path, e: std::io::Error) -> Error {
+    let hook = match ERROR_HOOK.read() {
+        Ok(h) => *h,
+        Err(e) => *e.into_inner()
+    };
+
+    if let Some(hook) = hook {
+        hook(action, p, &e);
+    }
+
+    match e.kind() {
+        std::io::ErrorKind::PermissionDenied => {
+            Error::Detail(ErrorKind::Forbidden, format!("permission denied"))
+        },
+        _ => Error::Content(format!("static file {} failed", action))
+    }
+}
+
+async fn open(p: &Path) -> Result<tokio::fs::File> {
+    tokio::fs::File::open(p).await.map_err(|e| fs_error("open", p, e))
+}
+
+/// a strong validator from modification time and size, like nginx
+pub fn etag(meta: &FileMeta) -> EntityTag {
+    let mtime = meta.modified
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+
+    EntityTag::strong(&format!("{:x}-{:x}", mtime, meta.len))
+}
+
+impl StaticFiles {
+    pub fn new<P: Into<PathBuf>>(root: P, mut options: StaticOptions) -> Self {
+        let cache = options.cache.take().map(AssetCache::new);
+
+        Self {
+            root: root.into(),
+            options: options,
+            cache: cache
+        }
+    }
+
+    pub fn cache(&self) -> Option<&AssetCache> {
+        self.cache.as_ref()
+    }
+
+    async fn stat(&self, p: &Path) -> Result<Option<FileMeta>> {
+        match &self.cache {
+            Some(c) => c.stat(p).await,
+            None => cache::stat(p).await
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub async fn serve(&self, request: &RequestHeader, target: &ByteSlice) -> Result<ResponseContent> {
+        let content = self.resolve(request, target).await?;
+        let content = conditional::apply(request, content)?;
+
+        range::apply(request, content)
+    }
+
+    async fn resolve(&self, request: &RequestHeader, target: &ByteSlice) -> Result<ResponseContent> {
+        if let Some(r) = reject_method(request) {
+            return r;
+        }
+
+        let path = request_path(target, &self.options)?;
+
+        let mut fs_path = self.root.clone();
+        for seg in path::segments(&path) {
+            fs_path.push(seg);
+        }
+
+        let meta = match self.stat(&fs_path).await? {
+            Some(m) => m,
+            None => {
+                return self.fallback(request, &path).await;
+            }
+        };
+
+        if !meta.is_dir {
+            return self.file(request, &fs_path, &meta).await;
+        }
+
+        if !path.ends_with('/') {
+            return ResponseBuilder::new()
+                .status(StatusCode::MOVED_PERMANENTLY)
+                .header("Location", &format!("{}/", path::encode(&path)))
+                .build();
+        }
+
+        if let Some(index) = &self.options.index {
+            let index_path = fs_path.join(index);
+            if let Some(m) = self.stat(&index_path).await? {
+                if m.is_file {
+                    return self.file(request, &index_path, &m).await;
+                }
+            }
+        }
+
+        if self.options.listing {
+            let html = listing::render(&fs_path, &path, self.options.hidden).await?;
+            return ResponseBuilder::new().html(&html).build();
+        }
+
+        Err(Error::Detail(ErrorKind::Forbidden, format!("no index for {}", path)))
+    }
+
+    async fn fallback(&self, request: &RequestHeader, path: &str) -> Result<ResponseContent> {
+        if !self.options.spa_fallback {
+            return Err(not_found(path));
+        }
+
+        let index = self.options.index.as_ref().map(|i| i.as_str()).unwrap_or("index.html");
+        let index_path = self.root.join(index);
+
+        match self.stat(&index_path).await? {
+            Some(m) if m.is_file => self.file(request, &index_path, &m).await,
+            _ => Err(not_found(path))
+        }
+    }
+
+    /// the best precompressed sibling of `fs_path` the client accepts, as
+    /// (coding, path, metadata); the bool tells whether any sibling exists
+    async fn precompressed(&self, request: &RequestHeader, fs_path: &Path)
+        -> Result<(bool, Option<(&'static str, PathBuf, FileMeta)>)> {
+        if !self.options.precompressed {
+            return Ok((false, None));
+        }
+
+        let mut found = Vec::new();
+        for (coding, ext) in PRECOMPRESSED {
+            let mut name = fs_path.as_os_str().to_os_string();
+            name.push(".");
+            name.push(ext);
+            let p = PathBuf::from(name);
+
+            if let Some(m) = self.stat(&p).await? {
+                if m.is_file {
+                    found.push((*coding, p, m));
+                }
+            }
+        }
+
+        if found.is_empty() {
+            return Ok((false, None));
+        }
+
+        let offered: Vec<&'static str> = found.iter().map(|(c, _, _)| *c).collect();
+
+        let chosen = choose_encoding(request, &offered);
+
+        let variant = found.into_iter().find(|(c, _, _)| *c == chosen);
+
+        Ok((true, variant))
+    }
+
+    async fn file(&self, request: &RequestHeader, fs_path: &Path, meta: &FileMeta) -> Result<ResponseContent> {
+        let (vary, variant) = self.precompressed(request, fs_path).await?;
+
+        let (coding, send_path, meta) = match &variant {
+            Some((c, p, m)) => (Some(*c), p.as_path(), m),
+            None => (None, fs_path, meta)
+        };
+
+        let body = match self.cached(send_path, meta).await? {
+            Some(data) => Body::Shared(data),
+            None => Body::File(FileBody::new(open(send_path).await?, 0, meta.len))
+        };
+
+        // the type is the one of the uncompressed file
+        let mut builder = ResponseBuilder::new()
+            .header("Content-Type", mime::guess(fs_path))
+            .typed_header(&etag(meta));
+
+        if let Some(coding) = coding {
+            builder = builder.header("Content-Encoding", coding);
+        }
+
+        if vary {
+            builder = builder.append_header("Vary", "Accept-Encoding");
+        }
+
+        if let Some(modified) = meta.modified {
+            builder = builder.typed_header(&LastModified(HttpDate::from(modified)));
+        }
+
+        builder.body(body).build()
+    }
+
+    async fn cached(&self, p: &Path, meta: &FileMeta) -> Result<Option<std::sync::Arc<ByteSlice>>> {
+        match &self.cache {
+            Some(c) => c.read(p, meta).await,
+            None => Ok(None)
+        }
+    }
+}
+
+impl StaticHandler for StaticFiles {
+    fn serve<'a>(&'a self, request: &'a RequestHeader, target: &'a ByteSlice) -> ServeFuture<'a> {
+        Box::pin(StaticFiles::serve(self, request, target))
+    }
+}
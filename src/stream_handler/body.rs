@@ -0,0 +1,281 @@
+use crate::*;
+use crate::stream_handler::{SharedStream, BUF_SIZE};
+use crate::stream_handler::framing::BodyFraming;
+use crate::stream_handler::parser::trim_ows;
+use crate::stream_handler::spool;
+use crate::stream_handler::decompress::{ContentCoding, Decoder};
+use crate::header::ContentType;
+use crate::body::{urlencoded, json, charset, Format};
+
+const MAX_CHUNK_LINE: usize = 1024;
+
+enum State {
+    Remaining(usize),
+    ChunkSize,
+    ChunkData(usize),
+    Done
+}
+
+/// the remaining body bytes of one request, read on demand from the
+/// connection and decoded when it has a Content-Encoding; whatever is
+/// left unread must be `discard`ed before the next request is parsed
+/// from the same stream
+pub struct BodyStream {
+    stream: SharedStream,
+    state: State,
+    mode: ParseMode,
+    limits: Limits,
+    received: usize,
+    decoder: Option<Decoder>
+}
+
+fn parse_chunk_size(line: &ByteSlice) -> Result<usize> {
+    // chunk extensions are ignored
+    let size = match line.iter().position(|c| *c == b';') {
+        Some(i) => &line[..i],
+        None => line
+    };
+    let size = trim_ows(size);
+
+    if size.is_empty() || size.len() > 16 || !size.iter().all(|c| c.is_ascii_hexdigit()) {
+        return Err(Error::Detail(ErrorKind::BadRequest
+                , format!("invalid chunk size: {:?}", String::from_utf8_lossy(line))));
+    }
+
+    match usize::from_str_radix(std::str::from_utf8(size).unwrap(), 16) {
+        Ok(n) => Ok(n),
+        Err(_) => {
+            Err(Error::Detail(ErrorKind::BadRequest
+                    , format!("chunk size overflow")))
+        }
+    }
+}
+
+impl BodyStream {
+    /// the whole body length, when known upfront; never for a compressed body
+    pub fn len(&self) -> Option<usize> {
+        if self.decoder.is_some() {
+            return None;
+        }
+
+        match self.state {
+            State::Remaining(n) => Some(self.received + n),
+            State::Done => Some(self.received),
+            _ => None
+        }
+    }
+
+    /// bytes read from the connection, before decoding
+    pub fn received(&self) -> usize {
+        self.received
+    }
+
+    pub fn is_done(&self) -> bool {
+        match self.state {
+            State::Done => true,
+            _ => false
+        }
+    }
+
+    /// `None` once the body is complete
+    pub async fn next_chunk(&mut self) -> Result<Option<ByteArray>> {
+        let mut decoder = match self.decoder.take() {
+            Some(d) => d,
+            None => {
+                return self.next_raw().await;
+            }
+        };
+
+        // a chunk may decode to nothing, e.g. the gzip header alone
+        let r = loop {
+            let chunk = match self.next_raw().await {
+                Ok(Some(chunk)) => chunk,
+                Ok(None) => {
+                    // finishing again once done yields nothing
+                    break decoder.finish(self.received)
+                        .map(|out| if out.is_empty() { None } else { Some(out) });
+                },
+                Err(e) => break Err(e)
+            };
+
+            match decoder.write(&chunk, self.received) {
+                Ok(out) if out.is_empty() => {},
+                Ok(out) => break Ok(Some(out)),
+                Err(e) => break Err(e)
+            }
+        };
+
+        self.decoder = Some(decoder);
+        r
+    }
+
+    async fn next_raw(&mut self) -> Result<Option<ByteArray>> {
+        let mut stream = self.stream.lock().await;
+
+        loop {
+            match self.state {
+                State::Done => {
+                    return Ok(None);
+                },
+                State::Remaining(0) => {
+                    self.state = State::Done;
+                },
+                State::Remaining(n) => {
+                    let data = stream.read_some(std::cmp::min(n, BUF_SIZE)).await?;
+                    self.state = State::Remaining(n - data.len());
+                    self.received += data.len();
+                    return Ok(Some(data));
+                },
+                State::ChunkSize => {
+                    let line = stream.read_line(self.mode, MAX_CHUNK_LINE, ErrorKind::BadRequest).await?;
+                    let size = parse_chunk_size(&line)?;
+
+                    if size == 0 {
+                        // trailer fields are read and dropped
+                        stream.read_headers(self.mode, &self.limits).await?;
+                        self.state = State::Done;
+                        continue;
+                    }
+
+                    if self.received.saturating_add(size) > self.limits.body_size {
+                        return Err(Error::Detail(ErrorKind::PayloadTooLarge
+                                , format!("chunked body exceeds limit {}", self.limits.body_size)));
+                    }
+
+                    self.state = State::ChunkData(size);
+                },
+                State::ChunkData(0) => {
+                    let line = stream.read_line(self.mode, 0, ErrorKind::BadRequest).await?;
+                    if !line.is_empty() {
+                        return Err(Error::Detail(ErrorKind::BadRequest
+                                , format!("chunk data not followed by CRLF")));
+                    }
+
+                    self.state = State::ChunkSize;
+                },
+                State::ChunkData(n) => {
+                    let data = stream.read_some(std::cmp::min(n, BUF_SIZE)).await?;
+                    self.state = State::ChunkData(n - data.len());
+                    self.received += data.len();
+                    return Ok(Some(data));
+                }
+            }
+        }
+    }
+
+    pub async fn read_to_end(&mut self) -> Result<ByteArray> {
+        let mut buf = match self.len() {
+            Some(n) => Vec::with_capacity(n),
+            None => new_bytearray()
+        };
+
+        while let Some(chunk) = self.next_chunk().await? {
+            buf.extend_from_slice(&chunk);
+        }
+
+        Ok(buf)
+    }
+
+    /// skips the unread rest without decoding it, keeping the connection usable
+    pub async fn discard(&mut self) -> Result<()> {
+        self.decoder = None;
+
+        while let Some(_) = self.next_raw().await? {
+        }
+
+        Ok(())
+    }
+
+    pub fn new(stream: SharedStream, framing: BodyFraming
+               , mode: ParseMode, limits: Limits) -> Result<Self> {
+        let state = match framing {
+            BodyFraming::Empty => State::Done,
+            BodyFraming::ContentLength(n) => {
+                limits.check_body_size(n)?;
+                State::Remaining(n)
+            },
+            BodyFraming::Chunked => State::ChunkSize
+        };
+
+        Ok(Self {
+            stream: stream,
+            state: state,
+            mode: mode,
+            limits: limits,
+            received: 0,
+            decoder: None
+        })
+    }
+
+    /// decodes the body from `coding`, see `decompress::content_coding`
+    pub fn with_coding(mut self, coding: ContentCoding) -> Self {
+        self.decoder = Decoder::new(coding, self.limits);
+        self
+    }
+}
+
+fn expect_media_type(content_type: Option<&ContentType>, mime: &str) -> Result<()> {
+    match content_type {
+        Some(ct) if ct.is(mime) => Ok(()),
+        _ => {
+            Err(Error::Detail(ErrorKind::UnsupportedMediaType
+                    , format!("expected {}", mime)))
+        }
+    }
+}
+
+/// reads the body as the route asks for in `RegisterOptions::body_type`,
+/// decoded from `coding`
+pub async fn read_body(stream: &SharedStream, framing: BodyFraming, coding: ContentCoding
+                       , content_type: Option<&ContentType>
+                       , options: &RegisterOptions) -> Result<Body> {
+    let limits = options.limits_or(stream.lock().await.limits_ref());
+
+    let mut body = BodyStream::new(stream.clone(), framing, options.parse_mode, limits)?
+        .with_coding(coding);
+
+    match options.body_type {
+        BodyType::Stream => {
+            Ok(Body::Stream(body))
+        },
+        BodyType::Spool(threshold) => {
+            Ok(Body::Spool(spool::spool(&mut body, threshold, &options.spool_dir_or_temp()).await?))
+        },
+        BodyType::ByteArray => {
+            Ok(Body::ByteArray(body.read_to_end().await?))
+        },
+        BodyType::Utf8String => {
+            let charset = content_type.and_then(|ct| ct.charset()).unwrap_or("utf-8");
+            let s = charset::decode(body.read_to_end().await?, charset, options.charset_mode)?;
+            Ok(Body::Utf8String(s))
+        },
+        BodyType::UrlEncoded => {
+            expect_media_type(content_type, "application/x-www-form-urlencoded")?;
+            let charset = content_type.and_then(|ct| ct.charset());
+            Ok(Body::Form(urlencoded::parse(&body.read_to_end().await?, charset, options.charset_mode)?))
+        },
+        BodyType::Json(decoder) => {
+            match content_type {
+                Some(ct) if json::is_json(ct) => {},
+                _ => {
+                    return Err(Error::Detail(ErrorKind::UnsupportedMediaType
+                            , format!("expected application/json")));
+                }
+            }
+            Ok(Body::Json(decoder(&body.read_to_end().await?)?))
+        },
+        BodyType::Structured(decoder) => {
+            let format = match content_type.and_then(Format::from_content_type) {
+                Some(f) => f,
+                None => {
+                    return Err(Error::Detail(ErrorKind::UnsupportedMediaType
+                            , format!("expected json, msgpack or cbor")));
+                }
+            };
+            Ok(Body::Structured(format, decoder(format, &body.read_to_end().await?)?))
+        },
+        BodyType::Custom => {
+            Ok(Body::Custom(Box::new(body.read_to_end().await?)))
+        }
+    }
+}
@@ -0,0 +1,232 @@
+use std::io::Write;
+
+use crate::*;
+use crate::stream_handler::parser::{RawHeaders, trim_ows};
+
+/// decoded output up to this size is never refused for its ratio, small
+/// bodies of repetitive data compress far better than 100:1
+const RATIO_FLOOR: usize = 64 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContentCoding {
+    Identity,
+    Gzip,
+    Deflate,
+    Brotli
+}
+
+fn unsupported(desc: String) -> Error {
+    Error::Detail(ErrorKind::UnsupportedMediaType, desc)
+}
+
+impl ContentCoding {
+    pub fn parse(value: &ByteSlice) -> Result<Self> {
+        match trim_ows(value).to_ascii_lowercase().as_slice() {
+            b"identity" => Ok(ContentCoding::Identity),
+            b"gzip" | b"x-gzip" => Ok(ContentCoding::Gzip),
+            b"deflate" => Ok(ContentCoding::Deflate),
+            b"br" => Ok(ContentCoding::Brotli),
+            c => {
+                Err(unsupported(
+                        format!("unsupported content coding: {:?}", String::from_utf8_lossy(c))))
+            }
+        }
+    }
+}
+
+/// the coding of the request body from every Content-Encoding line; 415
+/// for codings we cannot decode and for more than one applied coding
+pub fn content_coding(headers: &RawHeaders) -> Result<ContentCoding> {
+    let mut coding = ContentCoding::Identity;
+
+    for (name, value) in headers {
+        if !name.eq_ignore_ascii_case(b"content-encoding") {
+            continue;
+        }
+
+        for c in value.split(|c| *c == b',') {
+            if trim_ows(c).is_empty() {
+                continue;
+            }
+
+            match ContentCoding::parse(c)? {
+                ContentCoding::Identity => {},
+                c if coding == ContentCoding::Identity => {
+                    coding = c;
+                },
+                _ => {
+                    return Err(unsupported(
+                            format!("stacked content codings are not supported")));
+                }
+            }
+        }
+    }
+
+    Ok(coding)
+}
+
+/// collects decoded bytes, failing the write past `limit`
+struct Sink {
+    buf: ByteArray,
+    limit: usize,
+    exceeded: bool
+}
+
+impl Write for Sink {
+    fn write(&mut self, data: &[u8]) -> std::io::Result<usize> {
+        if self.buf.len() + data.len() > self.limit {
+            self.exceeded = true;
+            return Err(std::io::Error::other("decoded body too large"));
+        }
+
+        self.buf.extend_from_slice(data);
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+enum Inner {
+    Gzip(flate2::write::GzDecoder<Sink>),
+    /// driven directly, the writer cannot tell a truncated stream from a complete one
+    Deflate(flate2::Decompress, Sink, bool),
+    Brotli(Box<brotli::DecompressorWriter<Sink>>)
+}
+
+impl Inner {
+    fn sink(&mut self) -> &mut Sink {
+        match self {
+            Inner::Gzip(d) => d.get_mut(),
+            Inner::Deflate(_, sink, _) => sink,
+            Inner::Brotli(d) => d.get_mut()
+        }
+    }
+}
+
+fn inflate(z: &mut flate2::Decompress, sink: &mut Sink, done: &mut bool, mut data: &ByteSlice) -> std::io::Result<()> {
+    let invalid = |desc: String| std::io::Error::new(std::io::ErrorKind::InvalidData, desc);
+
+    while !data.is_empty() {
+        if *done {
+            return Err(invalid(format!("data after the end of the deflate stream")));
+        }
+
+        let mut out = Vec::with_capacity(32 * 1024);
+        let before = z.total_in();
+        let status = z.decompress_vec(data, &mut out, flate2::FlushDecompress::None)
+            .map_err(|e| invalid(format!("{}", e)))?;
+        let consumed = (z.total_in() - before) as usize;
+        data = &data[consumed..];
+
+        sink.write_all(&out)?;
+
+        if status == flate2::Status::StreamEnd {
+            *done = true;
+        } else if consumed == 0 && out.is_empty() {
+            return Err(invalid(format!("invalid deflate stream")));
+        }
+    }
+
+    Ok(())
+}
+
+/// decodes the body as it is read, bounded by `Limits::body_size` and
+/// `Limits::decompress_ratio` against the bytes received so far
+pub(crate) struct Decoder {
+    inner: Inner,
+    limits: Limits,
+    decoded: usize
+}
+
+impl Decoder {
+    /// `None` for identity
+    pub fn new(coding: ContentCoding, limits: Limits) -> Option<Self> {
+        let sink = Sink {
+            buf: new_bytearray(),
+            limit: 0,
+            exceeded: false
+        };
+
+        let inner = match coding {
+            ContentCoding::Identity => {
+                return None;
+            },
+            ContentCoding::Gzip => Inner::Gzip(flate2::write::GzDecoder::new(sink)),
+            ContentCoding::Deflate => Inner::Deflate(flate2::Decompress::new(true), sink, false),
+            ContentCoding::Brotli => Inner::Brotli(Box::new(brotli::DecompressorWriter::new(sink, 4096)))
+        };
+
+        Some(Self {
+            inner: inner,
+            limits: limits,
+            decoded: 0
+        })
+    }
+
+    /// the most decoded bytes allowed after `received` encoded ones
+    fn limit(&self, received: usize) -> usize {
+        let ratio_limit = std::cmp::max(received.saturating_mul(self.limits.decompress_ratio), RATIO_FLOOR);
+
+        std::cmp::min(self.limits.body_size, ratio_limit)
+    }
+
+    fn set_limit(&mut self, received: usize) {
+        let limit = self.limit(received).saturating_sub(self.decoded);
+        self.inner.sink().limit = limit;
+    }
+
+    fn decoded(&mut self, r: std::io::Result<()>, received: usize) -> Result<ByteArray> {
+        let limit = self.limit(received);
+        let sink = self.inner.sink();
+        let out = std::mem::take(&mut sink.buf);
+
+        if sink.exceeded {
+            return Err(Error::Detail(ErrorKind::PayloadTooLarge
+                    , format!("decoded body exceeds limit {}", limit)));
+        }
+
+        if let Err(e) = r {
+            return Err(Error::Detail(ErrorKind::BadRequest
+                    , format!("invalid compressed body: {}", e)));
+        }
+
+        self.decoded += out.len();
+        Ok(out)
+    }
+
+    /// the output for `data`; `received` counts the encoded bytes so far,
+    /// `data` included
+    pub fn write(&mut self, data: &ByteSlice, received: usize) -> Result<ByteArray> {
+        self.set_limit(received);
+
+        let r = match &mut self.inner {
+            Inner::Gzip(d) => d.write_all(data),
+            Inner::Deflate(z, sink, done) => inflate(z, sink, done, data),
+            Inner::Brotli(d) => d.write_all(data)
+        };
+
+        self.decoded(r, received)
+    }
+
+    /// the rest of the output once the body is read; 400 when the
+    /// compressed data is truncated
+    pub fn finish(&mut self, received: usize) -> Result<ByteArray> {
+        self.set_limit(received);
+
+        let r = match &mut self.inner {
+            Inner::Gzip(d) => d.try_finish(),
+            Inner::Deflate(_, _, done) => {
+                if *done {
+                    Ok(())
+                } else {
+                    Err(std::io::Error::new(std::io::ErrorKind::UnexpectedEof, "truncated deflate stream"))
+                }
+            },
+            Inner::Brotli(d) => d.close()
+        };
+
+        self.decoded(r, received)
+    }
+}
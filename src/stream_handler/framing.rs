@@ -0,0 +1,109 @@
+use crate::*;
+use crate::header::is_token_char;
+use crate::stream_handler::parser::{RawHeaders, trim_ows};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BodyFraming {
+    Empty,
+    ContentLength(usize),
+    Chunked
+}
+
+fn bad_framing(desc: String) -> Error {
+    Error::Detail(ErrorKind::BadRequest, desc)
+}
+
+fn parse_content_length(value: &ByteSlice) -> Result<usize> {
+    // 1*DIGIT only, no sign, no list and no surrounding garbage
+    if value.is_empty() || !value.iter().all(|c| c.is_ascii_digit()) {
+        return Err(bad_framing(
+                format!("invalid Content-Length: {:?}", String::from_utf8_lossy(value))));
+    }
+
+    let mut n: usize = 0;
+    for c in value {
+        n = match n.checked_mul(10).and_then(|n| n.checked_add((c - b'0') as usize)) {
+            Some(n) => n,
+            None => {
+                return Err(bad_framing(
+                        format!("Content-Length overflow")));
+            }
+        };
+    }
+
+    Ok(n)
+}
+
+/// decides the request body length following RFC 9112 6.3, refusing
+/// everything a front proxy could read differently
+pub fn body_framing(version: &Version, headers: &RawHeaders) -> Result<BodyFraming> {
+    let mut content_length: Option<usize> = None;
+    let mut codings: Vec<ByteArray> = Vec::new();
+    let mut has_te = false;
+
+    for (name, value) in headers {
+        if name.eq_ignore_ascii_case(b"content-length") {
+            if content_length.is_some() {
+                return Err(bad_framing(
+                        format!("duplicate Content-Length")));
+            }
+
+            content_length = Some(parse_content_length(value)?);
+        } else if name.eq_ignore_ascii_case(b"transfer-encoding") {
+            has_te = true;
+
+            for coding in value.split(|c| *c == b',') {
+                let coding = trim_ows(coding).to_ascii_lowercase();
+                if coding.is_empty() {
+                    return Err(bad_framing(
+                            format!("empty transfer coding")));
+                }
+
+                // transfer-parameters are not supported by any coding we accept
+                if !coding.iter().all(|c| is_token_char(*c)) {
+                    return Err(bad_framing(
+                            format!("invalid transfer coding: {:?}", String::from_utf8_lossy(&coding))));
+                }
+
+                codings.push(coding);
+            }
+        }
+    }
+
+    if !has_te {
+        return match content_length {
+            Some(n) => Ok(BodyFraming::ContentLength(n)),
+            None => Ok(BodyFraming::Empty)
+        };
+    }
+
+    if content_length.is_some() {
+        return Err(bad_framing(
+                format!("both Transfer-Encoding and Content-Length")));
+    }
+
+    if version.version() == b"HTTP/1.0" {
+        return Err(bad_framing(
+                format!("Transfer-Encoding in a HTTP/1.0 request")));
+    }
+
+    match codings.last() {
+        Some(c) if c == b"chunked" => {},
+        _ => {
+            return Err(bad_framing(
+                    format!("chunked is not the final transfer coding")));
+        }
+    }
+
+    if codings.iter().filter(|c| *c == b"chunked").count() > 1 {
+        return Err(bad_framing(
+                format!("chunked applied more than once")));
+    }
+
+    if codings.len() > 1 {
+        return Err(Error::Detail(ErrorKind::NotImplemented
+                , format!("unsupported transfer coding: {:?}", String::from_utf8_lossy(&codings[0]))));
+    }
+
+    Ok(BodyFraming::Chunked)
+}
@@ -2,11 +2,14 @@ use std::sync;
 use std::collections;
 
 use crate::*;
-use crate::stream;
+use crate::stream_handler::Backtrace;
+
+const WILDCARD: u8 = b'*';
 
 pub struct TrieNode<T> {
     data: Option<T>,
-    nodes: collections::HashMap<Option<u8>, sync::Arc<sync::RwLock<TrieNode<T>>>>
+    nodes: collections::HashMap<Option<u8>, sync::Arc<sync::RwLock<TrieNode<T>>>>,
+    prefix: Option<sync::Arc<sync::RwLock<TrieNode<T>>>>
 }
 
 impl<T> TrieNode<T> {
@@ -38,6 +41,18 @@ impl<T> TrieNode<T> {
         self.nodes.contains_key(u)
     }
 
+    fn insert_prefix(&mut self, node: sync::Arc<sync::RwLock<TrieNode<T>>>) {
+        self.prefix = Some(node);
+    }
+
+    fn get_prefix_clone(&self) -> Option<sync::Arc<sync::RwLock<TrieNode<T>>>> {
+        if let Some(n) = &self.prefix {
+            Some(n.clone())
+        } else {
+            None
+        }
+    }
+
     pub fn data_mut(&mut self) -> &mut Option<T> {
         &mut self.data
     }
@@ -49,24 +64,36 @@ impl<T> TrieNode<T> {
     fn new(data: Option<T>) -> Self {
         Self {
             data: data,
-            nodes: collections::HashMap::new()
+            nodes: collections::HashMap::new(),
+            prefix: None
         }
     }
 }
 
 pub struct Match<T> {
-    node: sync::Arc<sync::RwLock<TrieNode<T>>>
+    node: sync::Arc<sync::RwLock<TrieNode<T>>>,
+    prefix: Option<sync::Arc<sync::RwLock<TrieNode<T>>>>,
+    missed: bool
 }
 
 impl<T> Match<T> {
     pub fn matched(&mut self, b: &u8) -> bool {
+        if self.missed {
+            return false;
+        }
+
         let n = match self.node.read().unwrap().get_clone(&Some(b.to_ascii_lowercase())) {
             Some(n) => n,
             None => {
+                self.missed = true;
                 return false;
             }
         };
 
+        if let Some(p) = n.read().unwrap().get_prefix_clone() {
+            self.prefix = Some(p);
+        }
+
         *&mut self.node = n;
 
         true
@@ -76,9 +103,25 @@ impl<T> Match<T> {
         self.node.read().unwrap().get_clone(&None).unwrap()
     }
 
+    /// exact leaf of the matched bytes, or the longest `prefix*` pattern seen on the way
+    pub fn get_clone(&self) -> Option<sync::Arc<sync::RwLock<TrieNode<T>>>> {
+        if self.missed {
+            return self.prefix.clone();
+        }
+
+        match self.node.read().unwrap().get_clone(&None) {
+            Some(n) => Some(n),
+            None => self.prefix.clone()
+        }
+    }
+
     pub fn new(node: sync::Arc<sync::RwLock<TrieNode<T>>>) -> Self {
+        let prefix = node.read().unwrap().get_prefix_clone();
+
         Self {
-            node: node
+            node: node,
+            prefix: prefix,
+            missed: false
         }
     }
 }
@@ -93,6 +136,11 @@ impl<T> TrieTree<T> {
             return Err(Error::Simple(ErrorKind::RouteIsEmpty));
         }
 
+        let (data, is_prefix) = match data.split_last() {
+            Some((last, rest)) if *last == WILDCARD => (rest, true),
+            _ => (data, false)
+        };
+
         let mut node = self.root.clone();
 
         for item in data {
@@ -113,6 +161,24 @@ impl<T> TrieTree<T> {
             node = n;
         }
 
+        if is_prefix {
+            let (prefix_node, is) = {
+                match node.read().unwrap().get_prefix_clone() {
+                    Some(n) => {
+                        (n, true)
+                    },
+                    None => {
+                        (node.read().unwrap().new_none(t), false)
+                    }
+                }
+            };
+            if !is {
+                node.write().unwrap().insert_prefix(prefix_node.clone());
+            }
+
+            return Ok((prefix_node, is));
+        }
+
         let (leaf_node, is) = {
             match node.read().unwrap().get_clone(&None) {
                 Some(n) => {
@@ -130,17 +196,20 @@ impl<T> TrieTree<T> {
         Ok((leaf_node, is))
     }
 
-    pub async fn find_from_stream<S: stream::Stream>(
-        &self, stream: &mut S, length: &mut usize) -> Result<sync::Arc<sync::RwLock<TrieNode<T>>>> {
+    /// looks up the field name ahead of `stream`, stopping before the
+    /// first `' '` or `':'`, which `length` counts
+    pub async fn find_from_stream(
+        &self, stream: &mut Backtrace<'_>, length: &mut usize) -> Result<sync::Arc<sync::RwLock<TrieNode<T>>>> {
 
         *length = 0;
 
         let mut node = self.root.clone();
+        let mut prefix = node.read().unwrap().get_prefix_clone();
 
         loop {
             let item = match stream.lookup_next_one().await {
                 Ok(v) => v,
-                Err(err) => {
+                Err(_) => {
                     return Err(Error::Simple(ErrorKind::NotMatched));
                 }
             };
@@ -151,17 +220,47 @@ impl<T> TrieTree<T> {
                 break;
             }
 
-            let n = match node.read().unwrap().get_clone(&Some(item.to_ascii_lowercase())) {
+            // the guard must not live across the awaits below
+            let next = node.read().unwrap().get_clone(&Some(item.to_ascii_lowercase()));
+            let n = match next {
                 Some(n) => {
                     n
                 },
                 None => {
-                    return Err(Error::Content(
-                            format!("HeaderTrietree::find_from_stream get {} from node, not found"
-                                    , item)));
+                    if prefix.is_none() {
+                        return Err(Error::Content(
+                                format!("HeaderTrietree::find_from_stream get {} from node, not found"
+                                        , item)));
+                    }
+
+                    // covered by a prefix pattern, consume the rest of the name
+                    stream.skip_next_one();
+
+                    loop {
+                        let item = match stream.lookup_next_one().await {
+                            Ok(v) => v,
+                            Err(_) => {
+                                return Err(Error::Simple(ErrorKind::NotMatched));
+                            }
+                        };
+
+                        *length += 1;
+
+                        if item == b' ' || item == b':' {
+                            break;
+                        }
+
+                        stream.skip_next_one();
+                    }
+
+                    return Ok(prefix.unwrap());
                 }
             };
 
+            if let Some(p) = n.read().unwrap().get_prefix_clone() {
+                prefix = Some(p);
+            }
+
             node = n;
 
             stream.skip_next_one();
@@ -172,7 +271,14 @@ impl<T> TrieTree<T> {
                 n
             },
             None => {
-                return Err(Error::Simple(ErrorKind::NotMatched));
+                match prefix {
+                    Some(p) => {
+                        p
+                    },
+                    None => {
+                        return Err(Error::Simple(ErrorKind::NotMatched));
+                    }
+                }
             }
         };
 
@@ -187,6 +293,7 @@ impl<T> TrieTree<T> {
         *length = 0;
 
         let mut node = self.root.clone();
+        let mut prefix = node.read().unwrap().get_prefix_clone();
 
         for item in data {
             *length += 1;
@@ -195,15 +302,34 @@ impl<T> TrieTree<T> {
                 break;
             }
 
-            let n = match node.read().unwrap().get_clone(&Some(*item)) {
+            let n = match node.read().unwrap().get_clone(&Some(item.to_ascii_lowercase())) {
                 Some(n) => {
                     n
                 },
                 None => {
-                    return Err(Error::Simple(ErrorKind::RouteIsEmpty));
+                    match prefix {
+                        Some(p) => {
+                            for item in &data[*length..] {
+                                *length += 1;
+
+                                if *item == b' ' {
+                                    break;
+                                }
+                            }
+
+                            return Ok(p);
+                        },
+                        None => {
+                            return Err(Error::Simple(ErrorKind::RouteIsEmpty));
+                        }
+                    }
                 }
             };
 
+            if let Some(p) = n.read().unwrap().get_prefix_clone() {
+                prefix = Some(p);
+            }
+
             node = n;
         }
 
@@ -212,7 +338,14 @@ impl<T> TrieTree<T> {
                 n
             },
             None => {
-                return Err(Error::Simple(ErrorKind::NotMatched));
+                match prefix {
+                    Some(p) => {
+                        p
+                    },
+                    None => {
+                        return Err(Error::Simple(ErrorKind::NotMatched));
+                    }
+                }
             }
         };
 
@@ -229,4 +362,3 @@ impl<T> TrieTree<T> {
         }
     }
 }
-
@@ -86,7 +86,7 @@ impl<'a> Backtrace<'a> {
 }
 
 impl<'a> Backtrace<'a> {
-    fn new(stream: &'a mut Stream) -> Self {
+    pub fn new(stream: &'a mut Stream) -> Self {
         Self {
             stream: stream,
             index: 0
@@ -95,11 +95,15 @@ impl<'a> Backtrace<'a> {
 }
 
 /// ////////////////////////////////
+pub type SharedStream = Arc<tokio::sync::Mutex<Stream>>;
+
 pub struct Stream {
     buffer: VecDeque<u8>,
 
     count: usize,
 
+    limits: Limits,
+
     stream: OwnedReadHalf,
 }
 
@@ -215,7 +219,20 @@ impl Stream {
         Backtrace::new(self)
     }
 
+    /// buffered bytes first, the socket only when the buffer is empty
+    async fn read_some(&mut self, max: usize) -> Result<ByteArray> {
+        if self.buffer.len() == 0 {
+            self.read_from_stream().await?;
+        }
+
+        let n = std::cmp::min(max, self.buffer.len());
+
+        self.take_n_vec(n)
+    }
+
     async fn read(&mut self, size: usize) -> Result<ByteArray> {
+        self.limits.check_body_size(size)?;
+
         let mut buf = Vec::with_capacity(size);
 
         let mut remain_len: usize = size;
@@ -308,13 +325,30 @@ impl Stream {
 
             count: 0 as usize,
 
+            limits: Limits::default(),
+
             stream: stream
         }
     }
+
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.limits = limits;
+    }
+
+    pub fn limits_ref(&self) -> &Limits {
+        &self.limits
+    }
 }
 
 pub(crate) mod opt;
 pub(crate) mod stream_parse_pool;
 pub(crate) mod handler_pool;
+pub mod header_trietree;
+pub mod parser;
+pub mod framing;
+pub mod body;
+pub mod spool;
+pub mod path;
+pub mod decompress;
 // pub(crate) mod url;
 // pub(crate) mod url_trietree;
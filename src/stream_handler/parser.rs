@@ -0,0 +1,275 @@
+use crate::*;
+use crate::header::is_token_char;
+use crate::stream_handler::Stream;
+
+pub type RawHeaders = Vec<(ByteArray, ByteArray)>;
+
+pub struct RequestLine {
+    pub method: Method,
+    pub target: ByteArray,
+    pub version: Version
+}
+
+fn bad_request(desc: String) -> Error {
+    Error::Detail(ErrorKind::BadRequest, desc)
+}
+
+fn is_ows(c: u8) -> bool {
+    c == b' ' || c == b'\t'
+}
+
+pub(crate) fn trim_ows(v: &ByteSlice) -> &ByteSlice {
+    let start = match v.iter().position(|c| !is_ows(*c)) {
+        Some(i) => i,
+        None => {
+            return &v[0..0];
+        }
+    };
+    let end = v.iter().rposition(|c| !is_ows(*c)).unwrap();
+
+    &v[start..end + 1]
+}
+
+/// field-value may carry HTAB and visible / obs-text bytes, never CR, LF or NUL
+fn check_field_value(value: &ByteSlice, mode: ParseMode) -> Result<()> {
+    for c in value {
+        let ok = match mode {
+            ParseMode::Strict => *c == b'\t' || (*c >= 0x20 && *c != 0x7f),
+            ParseMode::Lenient => *c != b'\r' && *c != b'\n' && *c != 0
+        };
+
+        if !ok {
+            return Err(bad_request(
+                    format!("invalid byte 0x{:02x} in header value", c)));
+        }
+    }
+
+    Ok(())
+}
+
+pub fn parse_header_line(line: &ByteSlice, mode: ParseMode) -> Result<(ByteArray, ByteArray)> {
+    let colon = match line.iter().position(|c| *c == b':') {
+        Some(i) => i,
+        None => {
+            return Err(bad_request(
+                    format!("header line without ':'")));
+        }
+    };
+
+    let mut name = &line[..colon];
+
+    if let Some(c) = name.last() {
+        if is_ows(*c) {
+            match mode {
+                ParseMode::Strict => {
+                    return Err(bad_request(
+                            format!("whitespace between header name and ':'")));
+                },
+                ParseMode::Lenient => {
+                    name = trim_ows(name);
+                }
+            }
+        }
+    }
+
+    if name.is_empty() {
+        return Err(bad_request(
+                format!("empty header name")));
+    }
+
+    for c in name {
+        let ok = match mode {
+            ParseMode::Strict => is_token_char(*c),
+            ParseMode::Lenient => *c > 0x20 && *c != 0x7f
+        };
+
+        if !ok {
+            return Err(bad_request(
+                    format!("invalid byte 0x{:02x} in header name", c)));
+        }
+    }
+
+    let value = trim_ows(&line[colon + 1..]);
+    check_field_value(value, mode)?;
+
+    Ok((name.to_vec(), value.to_vec()))
+}
+
+pub fn parse_request_line(line: &ByteSlice, mode: ParseMode) -> Result<RequestLine> {
+    let parts: Vec<&ByteSlice> = match mode {
+        ParseMode::Strict => line.split(|c| *c == b' ').collect(),
+        ParseMode::Lenient => line.split(|c| is_ows(*c)).filter(|p| !p.is_empty()).collect()
+    };
+
+    if parts.len() != 3 {
+        return Err(bad_request(
+                format!("request line must have 3 parts, got {}", parts.len())));
+    }
+
+    let (method, target, version) = (parts[0], parts[1], parts[2]);
+
+    if method.is_empty() || !method.iter().all(|c| is_token_char(*c)) {
+        return Err(bad_request(
+                format!("invalid method token")));
+    }
+
+    if target.is_empty() || target.iter().any(|c| *c <= 0x20 || *c == 0x7f) {
+        return Err(bad_request(
+                format!("invalid request target")));
+    }
+
+    let version_ok = match mode {
+        ParseMode::Strict => {
+            version.len() == 8 && version.starts_with(b"HTTP/")
+                && version[5].is_ascii_digit() && version[6] == b'.'
+                && version[7].is_ascii_digit()
+        },
+        ParseMode::Lenient => {
+            version.len() > 5 && version[..5].eq_ignore_ascii_case(b"HTTP/")
+        }
+    };
+
+    if !version_ok {
+        return Err(bad_request(
+                format!("invalid http version")));
+    }
+
+    Ok(RequestLine {
+        method: Method::from_bytes(method),
+        target: target.to_vec(),
+        version: Version(version.to_vec())
+    })
+}
+
+const MAX_LEADING_EMPTY_LINES: usize = 8;
+
+fn too_large(kind: ErrorKind, what: &str, max: usize) -> Error {
+    Error::Detail(kind, format!("{} exceeds limit {}", what, max))
+}
+
+impl Stream {
+    /// the returned line has its CRLF (or, when lenient, bare LF) removed,
+    /// at most `max` bytes are buffered before failing with `kind`
+    pub async fn read_line(&mut self, mode: ParseMode, max: usize, kind: ErrorKind) -> Result<ByteArray> {
+        let mut scanned = 0;
+
+        loop {
+            if let Some(i) = self.buffer.range(scanned..).position(|c| *c == b'\n') {
+                // the terminator is not part of the limit
+                if scanned + i > max + 1 {
+                    return Err(too_large(kind, "line", max));
+                }
+
+                let mut line = self.take_n_vec(scanned + i + 1)?;
+                line.pop();
+
+                match line.last() {
+                    Some(b'\r') => {
+                        line.pop();
+                    },
+                    _ => {
+                        if mode == ParseMode::Strict {
+                            return Err(bad_request(
+                                    format!("bare LF line terminator")));
+                        }
+                    }
+                }
+
+                if mode == ParseMode::Strict && line.contains(&b'\r') {
+                    return Err(bad_request(
+                            format!("bare CR in line")));
+                }
+
+                if line.len() > max {
+                    return Err(too_large(kind, "line", max));
+                }
+
+                return Ok(line);
+            }
+
+            scanned = self.buffer.len();
+
+            if scanned > max + 1 {
+                return Err(too_large(kind, "line", max));
+            }
+
+            self.read_from_stream().await?;
+        }
+    }
+
+    pub async fn read_request_line(&mut self, mode: ParseMode) -> Result<RequestLine> {
+        let max = self.limits.request_line;
+        let mut empty_lines = 0;
+
+        loop {
+            let line = self.read_line(mode, max, ErrorKind::UriTooLong).await?;
+
+            // RFC 9112 2.2, at least one empty line before the request line should be ignored
+            if line.is_empty() {
+                empty_lines += 1;
+                if empty_lines > MAX_LEADING_EMPTY_LINES {
+                    return Err(bad_request(
+                            format!("too many empty lines before the request line")));
+                }
+                continue;
+            }
+
+            return parse_request_line(&line, mode);
+        }
+    }
+
+    /// `limits` are the route limits when known, see `RegisterOptions::limits_or`
+    pub async fn read_headers(&mut self, mode: ParseMode, limits: &Limits) -> Result<RawHeaders> {
+        let mut headers: RawHeaders = Vec::new();
+        let mut total: usize = 0;
+
+        loop {
+            let line = self.read_line(mode, limits.header_size, ErrorKind::HeaderFieldsTooLarge).await?;
+
+            if line.is_empty() {
+                break;
+            }
+
+            total += line.len() + 2;
+            if total > limits.header_bytes {
+                return Err(too_large(ErrorKind::HeaderFieldsTooLarge, "header section", limits.header_bytes));
+            }
+
+            if is_ows(line[0]) {
+                // obs-fold
+                if mode == ParseMode::Strict {
+                    return Err(bad_request(
+                            format!("obsolete line folding")));
+                }
+
+                let (_, value) = match headers.last_mut() {
+                    Some(h) => h,
+                    None => {
+                        return Err(bad_request(
+                                format!("line folding before the first header")));
+                    }
+                };
+
+                let folded = trim_ows(&line);
+                check_field_value(folded, mode)?;
+
+                if value.len() + 1 + folded.len() > limits.header_size {
+                    return Err(too_large(ErrorKind::HeaderFieldsTooLarge, "folded header", limits.header_size));
+                }
+
+                value.push(b' ');
+                value.extend_from_slice(folded);
+
+                continue;
+            }
+
+            if headers.len() >= limits.header_count {
+                return Err(too_large(ErrorKind::HeaderFieldsTooLarge, "header count", limits.header_count));
+            }
+
+            headers.push(parse_header_line(&line, mode)?);
+        }
+
+        Ok(headers)
+    }
+}
@@ -0,0 +1,96 @@
+use crate::*;
+use crate::body::urlencoded::percent_decode;
+
+fn bad(target: &ByteSlice, why: &str) -> Error {
+    Error::Detail(ErrorKind::BadRequest
+            , format!("{}: {}", why, String::from_utf8_lossy(target)))
+}
+
+/// the path of an origin-form request target, percent-decoded with `.` and
+/// `..` segments resolved (RFC 3986 5.2.4) and repeated slashes collapsed;
+/// climbing above the root, an encoded `/` or `\`, or NUL is a 400
+pub fn normalize(target: &ByteSlice) -> Result<String> {
+    let end = target.iter()
+        .position(|c| *c == b'?' || *c == b'#')
+        .unwrap_or(target.len());
+    let raw = &target[..end];
+
+    if !raw.starts_with(b"/") {
+        return Err(bad(target, "request path must start with /"));
+    }
+
+    let mut segments: Vec<String> = Vec::new();
+    // `/a/` and `/a/b/..` both name the directory `/a/`
+    let mut trailing = false;
+
+    for seg in raw.split(|c| *c == b'/') {
+        let decoded = percent_decode(seg, false)?;
+
+        if decoded.iter().any(|c| *c == b'/' || *c == b'\\' || *c == 0) {
+            return Err(bad(target, "invalid byte in request path segment"));
+        }
+
+        let decoded = match String::from_utf8(decoded) {
+            Ok(s) => s,
+            Err(_) => {
+                return Err(bad(target, "request path is not valid utf-8"));
+            }
+        };
+
+        trailing = decoded.is_empty() || decoded == "." || decoded == "..";
+
+        match decoded.as_str() {
+            "" | "." => {},
+            ".." => {
+                if segments.pop().is_none() {
+                    return Err(bad(target, "request path escapes the root"));
+                }
+            },
+            _ => {
+                segments.push(decoded);
+            }
+        }
+    }
+
+    let mut path = String::with_capacity(raw.len());
+    for seg in &segments {
+        path.push('/');
+        path.push_str(seg);
+    }
+
+    if path.is_empty() || trailing {
+        path.push('/');
+    }
+
+    Ok(path)
+}
+
+/// the normalized segments, without empty ones
+pub fn segments(path: &str) -> impl Iterator<Item = &str> {
+    path.split('/').filter(|s| !s.is_empty())
+}
+
+/// percent-encodes everything but pchar (RFC 3986 3.3), for links and Location
+pub fn encode_segment(seg: &str) -> String {
+    let mut out = String::with_capacity(seg.len());
+
+    for c in seg.bytes() {
+        match c {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9'
+                | b'-' | b'.' | b'_' | b'~'
+                | b'!' | b'$' | b'&' | b'\'' | b'(' | b')' | b'*' | b'+' | b',' | b';' | b'='
+                | b':' | b'@' => {
+                out.push(c as char);
+            },
+            _ => {
+                out.push_str(&format!("%{:02X}", c));
+            }
+        }
+    }
+
+    out
+}
+
+pub fn encode(path: &str) -> String {
+    path.split('/').map(encode_segment).collect::<Vec<String>>().join("/")
+}
@@ -0,0 +1,138 @@
+use tokio::io::AsyncWriteExt;
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::*;
+use crate::stream_handler::body::BodyStream;
+
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// removed from disk when dropped
+pub struct TempFile {
+    path: PathBuf,
+    len: u64
+}
+
+impl TempFile {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    pub async fn open(&self) -> Result<tokio::fs::File> {
+        match tokio::fs::File::open(&self.path).await {
+            Ok(f) => Ok(f),
+            Err(e) => {
+                Err(Error::Content(
+                        format!("failed to open spool file {:?}: {:?}", self.path, e)))
+            }
+        }
+    }
+
+    /// created with mode 0600 on unix, the body may hold anything the client sent
+    async fn create(dir: &Path) -> Result<(Self, tokio::fs::File)> {
+        loop {
+            let path = dir.join(format!(
+                    "tokio_httpserver-{}-{}.body"
+                    , std::process::id(), NEXT_ID.fetch_add(1, Ordering::Relaxed)));
+
+            let mut options = tokio::fs::OpenOptions::new();
+            options.write(true).create_new(true);
+            #[cfg(unix)]
+            options.mode(0o600);
+
+            match options.open(&path).await {
+                Ok(f) => {
+                    return Ok((Self {
+                        path: path,
+                        len: 0
+                    }, f));
+                },
+                Err(ref e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                    continue;
+                },
+                Err(e) => {
+                    return Err(Error::Content(
+                            format!("failed to create spool file {:?}: {:?}", path, e)));
+                }
+            }
+        }
+    }
+}
+
+/// the unlink is a blocking call, so inside a runtime it goes to the
+/// blocking pool rather than stalling a worker thread
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let path = std::mem::take(&mut self.path);
+
+        match tokio::runtime::Handle::try_current() {
+            Ok(handle) => {
+                handle.spawn_blocking(move || {
+                    let _ = std::fs::remove_file(&path);
+                });
+            },
+            Err(_) => {
+                let _ = std::fs::remove_file(&path);
+            }
+        }
+    }
+}
+
+pub enum SpoolBody {
+    Memory(ByteArray),
+    File(TempFile)
+}
+
+impl SpoolBody {
+    pub fn len(&self) -> u64 {
+        match self {
+            SpoolBody::Memory(v) => v.len() as u64,
+            SpoolBody::File(f) => f.len()
+        }
+    }
+}
+
+fn write_err(e: std::io::Error) -> Error {
+    Error::Content(format!("failed to write spool file: {:?}", e))
+}
+
+/// keeps the body in memory up to `threshold` bytes, moves it to a temp file
+/// in `dir` past that
+pub async fn spool(body: &mut BodyStream, threshold: usize, dir: &Path) -> Result<SpoolBody> {
+    let mut buf = new_bytearray();
+
+    loop {
+        let chunk = match body.next_chunk().await? {
+            Some(c) => c,
+            None => {
+                return Ok(SpoolBody::Memory(buf));
+            }
+        };
+
+        buf.extend_from_slice(&chunk);
+
+        if buf.len() > threshold {
+            break;
+        }
+    }
+
+    let (mut temp, mut file) = TempFile::create(dir).await?;
+
+    file.write_all(&buf).await.map_err(write_err)?;
+    temp.len = buf.len() as u64;
+    drop(buf);
+
+    while let Some(chunk) = body.next_chunk().await? {
+        file.write_all(&chunk).await.map_err(write_err)?;
+        temp.len += chunk.len() as u64;
+    }
+
+    file.flush().await.map_err(write_err)?;
+
+    Ok(SpoolBody::File(temp))
+}
@@ -0,0 +1,85 @@
+mod common;
+
+use std::sync::Arc;
+
+use tokio::io::AsyncReadExt;
+use tokio::sync::{RwLock, mpsc};
+
+use tokio_httpserver::*;
+use tokio_httpserver::response::{FileBody, ResponseBuilder};
+
+/// what the client reads before the connection ends, and how it ended
+async fn read_all(mut client: tokio::net::TcpStream) -> (Vec<u8>, std::io::Result<usize>) {
+    let mut wire = Vec::new();
+    let r = client.read_to_end(&mut wire).await;
+    (wire, r)
+}
+
+#[tokio::test]
+async fn failed_close_delimited_body_resets_the_connection() {
+    let (server, client) = common::tcp_pair().await;
+    let reader = tokio::spawn(read_all(client));
+
+    let (tx, rx) = mpsc::channel(2);
+    tx.send(Ok(b"partial".to_vec())).await.ok().unwrap();
+    tx.send(Err(Error::Content("upstream failed".to_string()))).await.ok().unwrap();
+    drop(tx);
+
+    let mut content = ResponseBuilder::new().body(Body::Channel(rx)).build().ok().unwrap();
+    content.captial.set_version(b"HTTP/1.0");
+
+    // split like the server does, the read half outlives the failed response
+    let (read_half, write_half) = server.into_split();
+    let writer = Arc::new(RwLock::new(write_half));
+    assert!(Response::new(writer.clone(), content).send().await.is_err());
+    drop(writer);
+
+    let (_, r) = reader.await.unwrap();
+    match r {
+        Err(e) => assert_eq!(e.kind(), std::io::ErrorKind::ConnectionReset),
+        Ok(_) => panic!("a failed close-delimited body ended like a complete one")
+    }
+
+    drop(read_half);
+}
+
+#[tokio::test]
+async fn failed_file_body_resets_the_connection() {
+    let p = common::temp_file("abort-file", b"too short");
+    let file = tokio::fs::File::open(&p).await.unwrap();
+
+    let (server, client) = common::tcp_pair().await;
+    let reader = tokio::spawn(read_all(client));
+
+    let content = ResponseBuilder::new().body(Body::File(FileBody::new(file, 0, 4096))).build().ok().unwrap();
+    let writer = Arc::new(RwLock::new(server));
+    assert!(Response::new(writer.clone(), content).send().await.is_err());
+    drop(writer);
+
+    let (_, r) = reader.await.unwrap();
+    assert!(r.is_err());
+
+    std::fs::remove_file(&p).unwrap();
+}
+
+#[tokio::test]
+async fn complete_close_delimited_body_ends_cleanly() {
+    let (server, client) = common::tcp_pair().await;
+    let reader = tokio::spawn(read_all(client));
+
+    let (tx, rx) = mpsc::channel(1);
+    tx.send(Ok(b"whole".to_vec())).await.ok().unwrap();
+    drop(tx);
+
+    let mut content = ResponseBuilder::new().body(Body::Channel(rx)).build().ok().unwrap();
+    content.captial.set_version(b"HTTP/1.0");
+
+    let (_read_half, write_half) = server.into_split();
+    let writer = Arc::new(RwLock::new(write_half));
+    Response::new(writer.clone(), content).send().await.ok().unwrap();
+    drop(writer);
+
+    let (wire, r) = reader.await.unwrap();
+    r.unwrap();
+    assert_eq!(common::body_of(&wire), b"whole");
+}
@@ -0,0 +1,45 @@
+use tokio_httpserver::body::{charset, CharsetMode};
+
+fn decode(v: &[u8], cs: &str, mode: CharsetMode) -> Option<String> {
+    charset::decode(v.to_vec(), cs, mode).ok()
+}
+
+fn status(v: &[u8], cs: &str, mode: CharsetMode) -> u16 {
+    match charset::decode(v.to_vec(), cs, mode) {
+        Err(e) => e.status(),
+        Ok(s) => panic!("{} decoded to {:?}", cs, s)
+    }
+}
+
+#[test]
+fn declared_charsets() {
+    let strict = CharsetMode::Strict;
+
+    assert_eq!(decode(b"\xef\xbb\xbfcaf\xc3\xa9", "UTF-8", strict).as_deref(), Some("café"));
+    assert_eq!(decode(b"caf\xe9", "iso-8859-1", strict).as_deref(), Some("café"));
+    assert_eq!(decode(b"\x80 \x93q\x94", "windows-1252", strict).as_deref(), Some("\u{20ac} \u{201c}q\u{201d}"));
+    assert_eq!(decode(b"plain", " us-ascii ", strict).as_deref(), Some("plain"));
+    assert_eq!(decode(b"\x00h\x00i", "utf-16be", strict).as_deref(), Some("hi"));
+    assert_eq!(decode(b"h\x00i\x00", "utf-16le", strict).as_deref(), Some("hi"));
+    assert_eq!(decode(b"\xff\xfeh\x00i\x00", "utf-16", strict).as_deref(), Some("hi"));
+    assert_eq!(decode(b"\x00h\x00i", "utf-16", strict).as_deref(), Some("hi"));
+}
+
+#[test]
+fn invalid_bytes_follow_mode() {
+    assert_eq!(status(b"caf\xe9", "utf-8", CharsetMode::Strict), 400);
+    assert_eq!(status(b"caf\xe9", "us-ascii", CharsetMode::Strict), 400);
+    assert_eq!(status(b"\x81", "windows-1252", CharsetMode::Strict), 400);
+    assert_eq!(status(b"\x00h\x00", "utf-16be", CharsetMode::Strict), 400);
+    assert_eq!(status(b"\xd8\x00\x00h", "utf-16be", CharsetMode::Strict), 400);
+
+    assert_eq!(decode(b"caf\xe9", "utf-8", CharsetMode::Lossy).as_deref(), Some("caf\u{fffd}"));
+    assert_eq!(decode(b"a\x81b", "cp1252", CharsetMode::Lossy).as_deref(), Some("a\u{fffd}b"));
+    assert_eq!(decode(b"\x00h\x00", "utf-16be", CharsetMode::Lossy).as_deref(), Some("h\u{fffd}"));
+}
+
+#[test]
+fn unknown_charset_is_415() {
+    assert_eq!(status(b"abc", "koi8-r", CharsetMode::Strict), 415);
+    assert_eq!(status(b"abc", "koi8-r", CharsetMode::Lossy), 415);
+}
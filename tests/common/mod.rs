@@ -0,0 +1,123 @@
+#![allow(dead_code)]
+
+use tokio::io::AsyncWriteExt;
+use tokio::net::{TcpListener, TcpStream};
+
+use tokio_httpserver::*;
+use tokio_httpserver::stream_handler::Stream;
+use tokio_httpserver::stream_handler::body::BodyStream;
+use tokio_httpserver::stream_handler::framing::BodyFraming;
+
+/// a connected `(server, client)` pair over loopback
+pub async fn tcp_pair() -> (TcpStream, TcpStream) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let (accepted, client) = tokio::join!(listener.accept(), TcpStream::connect(addr));
+
+    (accepted.unwrap().0, client.unwrap())
+}
+
+/// the server side of a connection whose client writes `payload`, then closes
+pub async fn server_socket(payload: Vec<u8>) -> TcpStream {
+    let (server, mut client) = tcp_pair().await;
+
+    tokio::spawn(async move {
+        client.write_all(&payload).await.unwrap();
+    });
+
+    server
+}
+
+/// a request stream reading `payload`, as the server would
+pub async fn stream(payload: Vec<u8>) -> Stream {
+    let (reader, _writer) = server_socket(payload).await.into_split();
+    Stream::new(reader)
+}
+
+/// a body stream reading `payload` from a real socket, as the server would
+pub async fn body_stream(payload: Vec<u8>, framing: BodyFraming, limits: Limits) -> BodyStream {
+    let stream = std::sync::Arc::new(tokio::sync::Mutex::new(stream(payload).await));
+
+    BodyStream::new(stream, framing, ParseMode::Strict, limits).ok().unwrap()
+}
+
+/// `payload` framed by Content-Length
+pub async fn fixed_body(payload: Vec<u8>) -> BodyStream {
+    let len = payload.len();
+    body_stream(payload, BodyFraming::ContentLength(len), Limits::default()).await
+}
+
+/// `payload` sent as chunks of at most `size` bytes
+pub async fn chunked_body(payload: &[u8], size: usize, limits: Limits) -> BodyStream {
+    let mut wire = Vec::new();
+    for chunk in payload.chunks(size) {
+        wire.extend_from_slice(format!("{:x}\r\n", chunk.len()).as_bytes());
+        wire.extend_from_slice(chunk);
+        wire.extend_from_slice(b"\r\n");
+    }
+    wire.extend_from_slice(b"0\r\n\r\n");
+
+    body_stream(wire, BodyFraming::Chunked, limits).await
+}
+
+pub fn status<T>(r: Result<T>) -> u16 {
+    match r {
+        Ok(_) => 200,
+        Err(e) => e.status()
+    }
+}
+
+/// a GET request carrying `headers`
+pub fn get(headers: &[(&str, &str)]) -> RequestHeader {
+    let mut request = RequestHeader {
+        method: Method::Get,
+        ..Default::default()
+    };
+
+    for (name, value) in headers {
+        request.headers.insert(name.as_bytes(), HeaderValue::ByteArray(value.as_bytes().to_vec()));
+    }
+
+    request
+}
+
+/// what `Response::send` writes for `content`, head included
+pub async fn send(content: ResponseContent) -> (Result<()>, Vec<u8>) {
+    let writer = std::sync::Arc::new(tokio::sync::RwLock::new(Vec::new()));
+    let r = Response::new(writer.clone(), content).send().await;
+    let out = std::mem::take(&mut *writer.write().await);
+
+    (r, out)
+}
+
+/// the bytes after the head
+pub fn body_of(wire: &[u8]) -> &[u8] {
+    let i = wire.windows(4).position(|w| w == b"\r\n\r\n").unwrap();
+    &wire[i + 4..]
+}
+
+/// a temp file holding `data`, removed by the caller
+pub fn temp_file(name: &str, data: &[u8]) -> std::path::PathBuf {
+    let p = std::env::temp_dir().join(format!("tokio_httpserver-{}-{}", name, std::process::id()));
+    std::fs::write(&p, data).unwrap();
+    p
+}
+
+/// the payload of a complete chunked body, `None` when the last chunk is missing
+pub fn dechunk(mut body: &[u8]) -> Option<Vec<u8>> {
+    let mut out = Vec::new();
+
+    loop {
+        let i = body.windows(2).position(|w| w == b"\r\n")?;
+        let size = usize::from_str_radix(std::str::from_utf8(&body[..i]).ok()?, 16).ok()?;
+        body = &body[i + 2..];
+
+        if size == 0 {
+            return Some(out);
+        }
+
+        out.extend_from_slice(body.get(..size)?);
+        body = body.get(size + 2..)?;
+    }
+}
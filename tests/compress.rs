@@ -0,0 +1,91 @@
+use std::io::Read;
+
+use tokio_httpserver::*;
+use tokio_httpserver::response::{compress, ResponseBuilder, CompressOptions, FileBody};
+
+mod common;
+
+fn text(len: usize) -> String {
+    "hello compression ".repeat(len / 18 + 1)[..len].to_string()
+}
+
+fn compressed(accept: &str, content: ResponseContent) -> ResponseContent {
+    let request = common::get(&[("Accept-Encoding", accept)]);
+    compress::apply(&request, content, &CompressOptions::default()).ok().unwrap()
+}
+
+fn coding(content: &ResponseContent) -> Option<&ByteSlice> {
+    content.headers.get("Content-Encoding")
+}
+
+#[test]
+fn gzip_body_round_trips() {
+    let s = text(4096);
+    let out = compressed("gzip", ResponseBuilder::new().text(&s).build().ok().unwrap());
+    assert_eq!(coding(&out), Some(&b"gzip"[..]));
+    assert_eq!(out.headers.get("Vary"), Some(&b"Accept-Encoding"[..]));
+
+    let data = match out.body {
+        Body::ByteArray(v) => v,
+        _ => panic!("memory body not compressed in memory")
+    };
+    let mut decoded = String::new();
+    flate2::read::GzDecoder::new(&data[..]).read_to_string(&mut decoded).unwrap();
+    assert_eq!(decoded, s);
+}
+
+#[test]
+fn refused_codings_are_not_used() {
+    let body = || ResponseBuilder::new().text(&text(4096)).build().ok().unwrap();
+
+    assert_eq!(coding(&compressed("br;q=0, gzip", body())), Some(&b"gzip"[..]));
+    assert_eq!(coding(&compressed("gzip;q=0", body())), None);
+    assert_eq!(coding(&compressed("*;q=0, identity", body())), None);
+    assert_eq!(coding(&compressed("identity", body())), None);
+}
+
+#[test]
+fn small_and_binary_bodies_are_skipped() {
+    let out = compressed("gzip", ResponseBuilder::new().text("short").build().ok().unwrap());
+    assert_eq!(coding(&out), None);
+    assert_eq!(out.headers.get("Vary"), Some(&b"Accept-Encoding"[..]));
+
+    let png = ResponseBuilder::new().header("Content-Type", "image/png")
+        .bytes(vec![0u8; 4096]).build().ok().unwrap();
+    let out = compressed("gzip", png);
+    assert_eq!(coding(&out), None);
+    assert!(out.headers.get("Vary").is_none());
+}
+
+#[tokio::test]
+async fn source_error_aborts_the_body() {
+    let (tx, rx) = tokio::sync::mpsc::channel(4);
+    tx.send(Ok(text(100).into_bytes())).await.unwrap();
+    tx.send(Err(Error::Content("upstream failed".to_string()))).await.unwrap();
+    drop(tx);
+
+    let content = ResponseBuilder::new().header("Content-Type", "text/plain")
+        .body(Body::Channel(rx)).build().ok().unwrap();
+    let out = compressed("gzip", content);
+    assert_eq!(coding(&out), Some(&b"gzip"[..]));
+
+    let (r, wire) = common::send(out).await;
+    assert!(r.is_err());
+    assert!(common::dechunk(common::body_of(&wire)).is_none());
+}
+
+#[tokio::test]
+async fn file_ending_early_aborts_the_body() {
+    let p = common::temp_file("short-compress", text(2048).as_bytes());
+    let file = tokio::fs::File::open(&p).await.unwrap();
+    // the file is shorter than the length it was opened with
+    let content = ResponseBuilder::new().header("Content-Type", "text/plain")
+        .body(Body::File(FileBody::new(file, 0, 4096))).build().ok().unwrap();
+    let out = compressed("gzip", content);
+
+    let (r, wire) = common::send(out).await;
+    assert!(r.is_err());
+    assert!(common::dechunk(common::body_of(&wire)).is_none());
+
+    std::fs::remove_file(&p).unwrap();
+}
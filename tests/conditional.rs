@@ -0,0 +1,121 @@
+use tokio_httpserver::*;
+use tokio_httpserver::header::{EntityTag, HttpDate};
+use tokio_httpserver::response::{conditional, ResponseBuilder};
+
+mod common;
+
+const LAST_MODIFIED: &str = "Wed, 21 Oct 2015 07:28:00 GMT";
+
+fn content() -> ResponseContent {
+    ResponseBuilder::new().text("hello")
+        .header("ETag", "\"v1\"")
+        .header("Last-Modified", LAST_MODIFIED)
+        .build().ok().unwrap()
+}
+
+fn status(headers: &[(&str, &str)]) -> u16 {
+    let out = conditional::apply(&common::get(headers), content()).ok().unwrap();
+    out.captial.status().as_u16()
+}
+
+#[test]
+fn if_none_match_is_not_modified() {
+    assert_eq!(status(&[("If-None-Match", "\"v1\"")]), 304);
+    assert_eq!(status(&[("If-None-Match", "W/\"v1\"")]), 304);
+    assert_eq!(status(&[("If-None-Match", "*")]), 304);
+    assert_eq!(status(&[("If-None-Match", "\"v0\"")]), 200);
+
+    let out = conditional::apply(&common::get(&[("If-None-Match", "\"v1\"")]), content()).ok().unwrap();
+    assert!(out.headers.get("Content-Type").is_none());
+    assert_eq!(out.headers.get("ETag"), Some(&b"\"v1\""[..]));
+}
+
+#[test]
+fn failed_if_match_is_412() {
+    assert_eq!(status(&[("If-Match", "\"v0\"")]), 412);
+    assert_eq!(status(&[("If-Match", "\"v1\"")]), 200);
+    // If-Match compares strongly
+    assert_eq!(status(&[("If-Match", "W/\"v1\"")]), 412);
+}
+
+#[test]
+fn if_none_match_takes_precedence_over_if_modified_since() {
+    let later = "Thu, 22 Oct 2015 07:28:00 GMT";
+
+    assert_eq!(status(&[("If-Modified-Since", later)]), 304);
+    assert_eq!(status(&[("If-Modified-Since", "Tue, 20 Oct 2015 07:28:00 GMT")]), 200);
+    assert_eq!(status(&[("If-None-Match", "\"v0\""), ("If-Modified-Since", later)]), 200);
+}
+
+fn request(method: Method, headers: &[(&str, &str)]) -> RequestHeader {
+    let mut request = common::get(headers);
+    request.method = method;
+    request
+}
+
+fn current() -> conditional::Validators {
+    conditional::Validators {
+        etag: Some(EntityTag::strong("v1")),
+        last_modified: Some(HttpDate::parse_str(LAST_MODIFIED).ok().unwrap())
+    }
+}
+
+/// a PUT handler over one stored value, checking before it writes
+fn put(stored: &mut Option<String>, request: &RequestHeader, value: &str) -> u16 {
+    let validators = stored.as_ref().map(|_| current());
+
+    if let Some(status) = conditional::check(request, validators.as_ref()) {
+        return status.as_u16();
+    }
+
+    *stored = Some(value.to_string());
+    204
+}
+
+#[test]
+fn unsafe_methods_are_checked_before_acting() {
+    let mut stored = Some("old".to_string());
+
+    assert_eq!(put(&mut stored, &request(Method::Put, &[("If-Match", "\"v0\"")]), "lost update"), 412);
+    assert_eq!(stored.as_deref(), Some("old"));
+
+    assert_eq!(put(&mut stored, &request(Method::Put, &[("If-Match", "\"v1\"")]), "new"), 204);
+    assert_eq!(stored.as_deref(), Some("new"));
+
+    // create only when absent
+    let create = request(Method::Put, &[("If-None-Match", "*")]);
+    assert_eq!(put(&mut stored, &create, "again"), 412);
+    assert_eq!(stored.as_deref(), Some("new"));
+
+    let mut empty = None;
+    assert_eq!(put(&mut empty, &create, "first"), 204);
+    assert_eq!(empty.as_deref(), Some("first"));
+
+    // no representation matches If-Match, not even *
+    let mut empty = None;
+    assert_eq!(put(&mut empty, &request(Method::Put, &[("If-Match", "*")]), "x"), 412);
+    assert!(empty.is_none());
+}
+
+#[test]
+fn check_follows_the_precondition_order() {
+    let v = current();
+    let check = |method: Method, headers: &[(&str, &str)]| {
+        conditional::check(&request(method, headers), Some(&v)).map(|s| s.as_u16())
+    };
+    let earlier = "Tue, 20 Oct 2015 07:28:00 GMT";
+
+    assert_eq!(check(Method::Delete, &[]), None);
+    assert_eq!(check(Method::Delete, &[("If-Unmodified-Since", earlier)]), Some(412));
+    assert_eq!(check(Method::Delete, &[("If-Unmodified-Since", LAST_MODIFIED)]), None);
+    // If-Match wins over If-Unmodified-Since
+    assert_eq!(check(Method::Delete, &[("If-Match", "\"v1\""), ("If-Unmodified-Since", earlier)]), None);
+
+    assert_eq!(check(Method::Get, &[("If-None-Match", "\"v1\"")]), Some(304));
+    assert_eq!(check(Method::Post, &[("If-None-Match", "\"v1\"")]), Some(412));
+    assert_eq!(check(Method::Get, &[("If-Modified-Since", LAST_MODIFIED)]), Some(304));
+    assert_eq!(check(Method::Post, &[("If-Modified-Since", LAST_MODIFIED)]), None);
+
+    // a malformed header is ignored
+    assert_eq!(check(Method::Put, &[("If-Unmodified-Since", "yesterday")]), None);
+}
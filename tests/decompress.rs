@@ -0,0 +1,86 @@
+use std::io::Write;
+
+use tokio_httpserver::*;
+use tokio_httpserver::stream_handler::framing::BodyFraming;
+use tokio_httpserver::stream_handler::decompress::{self, ContentCoding};
+
+mod common;
+
+/// json-like records that do not compress to nothing
+fn records(n: u32) -> Vec<u8> {
+    let mut x: u64 = 7;
+    (0..n).flat_map(|i| {
+        x = x.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        format!("{{\"id\":{},\"v\":{}}},", i, x >> 40).into_bytes()
+    }).collect()
+}
+
+fn encode(coding: ContentCoding, data: &[u8]) -> Vec<u8> {
+    match coding {
+        ContentCoding::Gzip => {
+            let mut e = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+            e.write_all(data).unwrap();
+            e.finish().unwrap()
+        },
+        ContentCoding::Deflate => {
+            let mut e = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+            e.write_all(data).unwrap();
+            e.finish().unwrap()
+        },
+        ContentCoding::Brotli => {
+            let mut e = brotli::CompressorWriter::new(Vec::new(), 4096, 5, 22);
+            e.write_all(data).unwrap();
+            e.into_inner()
+        },
+        ContentCoding::Identity => data.to_vec()
+    }
+}
+
+async fn decode(wire: Vec<u8>, coding: ContentCoding, limits: Limits) -> Result<Vec<u8>> {
+    let len = wire.len();
+    let body = common::body_stream(wire, BodyFraming::ContentLength(len), limits).await;
+    body.with_coding(coding).read_to_end().await
+}
+
+#[tokio::test]
+async fn codings_round_trip() {
+    let data = records(20000);
+
+    for coding in &[ContentCoding::Gzip, ContentCoding::Deflate, ContentCoding::Brotli] {
+        let r = decode(encode(*coding, &data), *coding, Limits::default()).await;
+        assert_eq!(r.ok().unwrap(), data, "{:?}", coding);
+    }
+}
+
+#[tokio::test]
+async fn ratio_limit_is_413() {
+    let data = vec![b'a'; 4 * 1024 * 1024];
+
+    for coding in &[ContentCoding::Gzip, ContentCoding::Deflate, ContentCoding::Brotli] {
+        let r = decode(encode(*coding, &data), *coding, Limits::default()).await;
+        assert_eq!(common::status(r), 413, "{:?}", coding);
+    }
+}
+
+#[tokio::test]
+async fn truncated_stream_is_400() {
+    let data = records(2000);
+
+    for coding in &[ContentCoding::Gzip, ContentCoding::Deflate, ContentCoding::Brotli] {
+        let mut wire = encode(*coding, &data);
+        wire.truncate(wire.len() / 2);
+
+        let r = decode(wire, *coding, Limits::default()).await;
+        assert_eq!(common::status(r), 400, "{:?}", coding);
+    }
+}
+
+#[test]
+fn unknown_and_stacked_codings_are_415() {
+    let headers = |v: &str| vec![(b"Content-Encoding".to_vec(), v.as_bytes().to_vec())];
+
+    assert!(decompress::content_coding(&headers("identity")).ok() == Some(ContentCoding::Identity));
+    assert!(decompress::content_coding(&headers("x-gzip")).ok() == Some(ContentCoding::Gzip));
+    assert_eq!(common::status(decompress::content_coding(&headers("compress"))), 415);
+    assert_eq!(common::status(decompress::content_coding(&headers("gzip, br"))), 415);
+}
@@ -0,0 +1,163 @@
+mod common;
+
+use tokio_httpserver::*;
+use tokio_httpserver::static_files::{embedded, EmbeddedAssets, EmbeddedFile, EmbeddedVariant, StaticFiles, StaticOptions};
+
+static ASSETS: &[EmbeddedFile] = &[
+    EmbeddedFile {
+        path: "/index.html",
+        data: b"<h1>home</h1>",
+        etag: "index",
+        mime: "text/html; charset=utf-8",
+        variants: &[]
+    },
+    EmbeddedFile {
+        path: "/js/app.js",
+        data: b"identity",
+        etag: "app",
+        mime: "text/javascript; charset=utf-8",
+        variants: &[
+            EmbeddedVariant { coding: "br", data: b"brotli", etag: "app-br" },
+            EmbeddedVariant { coding: "gzip", data: b"gzip", etag: "app-gz" }
+        ]
+    }
+];
+
+fn scratch_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("tokio_httpserver-{}-{}", name, std::process::id()));
+    std::fs::create_dir_all(dir.join("js")).unwrap();
+    dir
+}
+
+fn header<'a>(content: &'a ResponseContent, name: &str) -> Option<&'a str> {
+    content.headers.get(name).map(|v| std::str::from_utf8(v).unwrap())
+}
+
+/// (status, Content-Type, Content-Encoding, Vary, body)
+async fn summary(r: Result<ResponseContent>) -> (u16, Option<String>, Option<String>, Option<String>, Vec<u8>) {
+    let content = match r {
+        Ok(c) => c,
+        Err(e) => {
+            return (e.status(), None, None, None, Vec::new());
+        }
+    };
+
+    let fields = ["Content-Type", "Content-Encoding", "Vary"].iter()
+        .map(|n| header(&content, n).map(|v| v.to_string()))
+        .collect::<Vec<_>>();
+    let status = content.captial.status().as_u16();
+
+    let (r, wire) = common::send(content).await;
+    r.ok().unwrap();
+
+    (status, fields[0].clone(), fields[1].clone(), fields[2].clone(), common::body_of(&wire).to_vec())
+}
+
+#[test]
+fn generate_folds_siblings_into_variants() {
+    let dir = scratch_dir("generate");
+    std::fs::write(dir.join("index.html"), b"<h1>home</h1>").unwrap();
+    std::fs::write(dir.join("js").join("app.js"), b"identity").unwrap();
+    std::fs::write(dir.join("js").join("app.js.br"), b"brotli").unwrap();
+    std::fs::write(dir.join("js").join("app.js.gz"), b"gzip").unwrap();
+    // no app.css, so this stays a file of its own
+    std::fs::write(dir.join("app.css.gz"), b"orphan").unwrap();
+
+    let src = embedded::generate(&dir).unwrap();
+
+    assert_eq!(src.matches("EmbeddedFile {").count(), 3);
+    assert!(src.contains("path: \"/index.html\""));
+    assert!(src.contains("path: \"/js/app.js\""));
+    assert!(src.contains("path: \"/app.css.gz\""));
+    assert!(!src.contains("path: \"/js/app.js.gz\""));
+    assert!(src.contains("mime: \"text/javascript; charset=utf-8\""));
+    assert!(src.contains("mime: \"application/gzip\""));
+
+    // br before gzip, under app.js
+    let app = &src[src.find("path: \"/js/app.js\"").unwrap()..];
+    let br = app.find("coding: \"br\"").unwrap();
+    let gz = app.find("coding: \"gzip\"").unwrap();
+    assert!(br < gz && gz < app.find("EmbeddedFile {").unwrap_or(app.len()));
+
+    // entity-tags come from the content
+    assert_eq!(src.matches("etag: ").count(), 5);
+    assert!(src.contains(&format!("{:?}", tokio_httpserver::response::conditional::content_tag(b"brotli"))));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn serves_variants_per_accept_encoding() {
+    let assets = EmbeddedAssets::new(ASSETS, StaticOptions::default());
+    let js = Some("text/javascript; charset=utf-8".to_string());
+    let vary = Some("Accept-Encoding".to_string());
+
+    let r = summary(assets.serve(&common::get(&[]), b"/js/app.js").await).await;
+    assert_eq!(r, (200, js.clone(), None, vary.clone(), b"identity".to_vec()));
+
+    let r = summary(assets.serve(&common::get(&[("Accept-Encoding", "gzip, br")]), b"/js/app.js").await).await;
+    assert_eq!(r, (200, js.clone(), Some("br".to_string()), vary.clone(), b"brotli".to_vec()));
+
+    let r = summary(assets.serve(&common::get(&[("Accept-Encoding", "gzip")]), b"/js/app.js").await).await;
+    assert_eq!(r, (200, js.clone(), Some("gzip".to_string()), vary.clone(), b"gzip".to_vec()));
+
+    let request = common::get(&[("Accept-Encoding", "br")]);
+    let content = assets.serve(&request, b"/js/app.js").await.ok().unwrap();
+    assert_eq!(header(&content, "ETag"), Some("\"app-br\""));
+
+    let request = common::get(&[("Accept-Encoding", "br"), ("If-None-Match", "\"app-br\"")]);
+    let r = summary(assets.serve(&request, b"/js/app.js").await).await;
+    assert_eq!(r.0, 304);
+
+    let assets = EmbeddedAssets::new(ASSETS, StaticOptions {
+        precompressed: false,
+        ..Default::default()
+    });
+    let r = summary(assets.serve(&common::get(&[("Accept-Encoding", "br")]), b"/js/app.js").await).await;
+    assert_eq!(r, (200, js, None, None, b"identity".to_vec()));
+}
+
+#[tokio::test]
+async fn paths_resolve_like_static_files() {
+    let dir = scratch_dir("embedded-paths");
+    for f in ASSETS {
+        std::fs::write(dir.join(&f.path[1..]), f.data).unwrap();
+        for v in f.variants {
+            let ext = if v.coding == "br" { "br" } else { "gz" };
+            std::fs::write(dir.join(format!("{}.{}", &f.path[1..], ext)), v.data).unwrap();
+        }
+    }
+
+    let options = || StaticOptions {
+        spa_fallback: true,
+        ..Default::default()
+    };
+    let assets = EmbeddedAssets::new(ASSETS, options());
+    let files = StaticFiles::new(&dir, options());
+
+    let targets: &[&[u8]] = &[b"/", b"/index.html", b"/js", b"/js/", b"/js/app.js"
+        , b"/js/app.js.gz", b"/js/app.js.br", b"/missing/route", b"/.hidden"];
+    let accepts = [None, Some("br, gzip")];
+
+    for target in targets {
+        for accept in accepts.iter() {
+            let request = match accept {
+                Some(v) => common::get(&[("Accept-Encoding", v)]),
+                None => common::get(&[])
+            };
+
+            let embedded = summary(assets.serve(&request, target).await).await;
+            let on_disk = summary(files.serve(&request, target).await).await;
+            assert_eq!(embedded, on_disk, "{} {:?}", String::from_utf8_lossy(target), accept);
+        }
+    }
+
+    // a direct request for a compressed sibling gets its bytes as is
+    let r = summary(assets.serve(&common::get(&[]), b"/js/app.js.gz").await).await;
+    assert_eq!(r, (200, Some("application/gzip".to_string()), None, None, b"gzip".to_vec()));
+
+    let r = assets.serve(&common::get(&[]), b"/js").await.ok().unwrap();
+    assert_eq!(header(&r, "Location"), Some("/js/"));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
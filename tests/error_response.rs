@@ -0,0 +1,41 @@
+use tokio_httpserver::*;
+use tokio_httpserver::response::ResponseBuilder;
+
+fn render(err: Error) -> (u16, String) {
+    let content = ResponseBuilder::from_error(&err).build().ok().unwrap();
+
+    let body = match content.body {
+        Body::Utf8String(s) => s,
+        _ => panic!("error body is not text")
+    };
+
+    (content.captial.status().as_u16(), body)
+}
+
+#[test]
+fn client_errors_keep_their_message() {
+    let (status, body) = render(Error::Detail(ErrorKind::BadRequest
+            , "invalid JSON at line 1 column 7".to_string()));
+    assert_eq!(status, 400);
+    assert_eq!(body, "invalid JSON at line 1 column 7");
+
+    let (status, body) = render(Error::Simple(ErrorKind::NotMatched));
+    assert_eq!(status, 404);
+    assert_eq!(body, "Not Found");
+}
+
+#[test]
+fn server_errors_and_forbidden_hide_details() {
+    let (status, body) = render(Error::Content(
+            "failed to open \"/srv/www/secret\": Os { code: 5 }".to_string()));
+    assert_eq!(status, 500);
+    assert_eq!(body, "Internal Server Error");
+
+    let (status, body) = render(Error::Detail(ErrorKind::Forbidden, "\"/srv/www/private\"".to_string()));
+    assert_eq!(status, 403);
+    assert_eq!(body, "Forbidden");
+
+    let (status, body) = render(Error::Detail(ErrorKind::NotImplemented, "gzip transfer coding".to_string()));
+    assert_eq!(status, 501);
+    assert_eq!(body, "Not Implemented");
+}
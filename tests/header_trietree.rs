@@ -0,0 +1,77 @@
+use tokio_httpserver::stream_handler::Backtrace;
+use tokio_httpserver::stream_handler::header_trietree::TrieTree;
+
+mod common;
+
+fn tree() -> TrieTree<u32> {
+    let mut tree = TrieTree::new();
+    tree.push(b"Content-Type", 1).ok().unwrap();
+    tree.push(b"X-*", 2).ok().unwrap();
+    tree.push(b"X-Custom-*", 3).ok().unwrap();
+    tree
+}
+
+fn find(tree: &TrieTree<u32>, name: &[u8]) -> Option<u32> {
+    let mut length = 0;
+    tree.find(name, &mut length).ok().map(|n| n.read().unwrap().data_ref().unwrap())
+}
+
+#[test]
+fn exact_match() {
+    let tree = tree();
+    assert_eq!(find(&tree, b"Content-Type"), Some(1));
+    assert_eq!(find(&tree, b"content-TYPE"), Some(1));
+
+    let mut length = 0;
+    tree.find(b"Content-Type value", &mut length).ok().unwrap();
+    assert_eq!(length, 13);
+}
+
+#[test]
+fn longest_prefix() {
+    let tree = tree();
+    assert_eq!(find(&tree, b"X-Custom-Trace-Id"), Some(3));
+    assert_eq!(find(&tree, b"x-custom-"), Some(3));
+    assert_eq!(find(&tree, b"X-Custom"), Some(2));
+    assert_eq!(find(&tree, b"X-Other"), Some(2));
+
+    let mut m = tree.matched();
+    for b in b"X-Custom-Id" {
+        m.matched(b);
+    }
+    assert_eq!(m.get_clone().map(|n| n.read().unwrap().data_ref().unwrap()), Some(3));
+}
+
+#[test]
+fn miss() {
+    let tree = tree();
+    assert_eq!(find(&tree, b"Accept"), None);
+    // a path to a leaf is not a match by itself
+    assert_eq!(find(&tree, b"Content"), None);
+    assert_eq!(find(&tree, b"Content-Type-Options"), None);
+
+    let mut m = tree.matched();
+    for b in b"Accept" {
+        m.matched(b);
+    }
+    assert!(m.get_clone().is_none());
+}
+
+async fn find_from_stream(tree: &TrieTree<u32>, payload: &[u8]) -> (Option<u32>, usize) {
+    let mut stream = common::stream(payload.to_vec()).await;
+    let mut backtrace = Backtrace::new(&mut stream);
+
+    let mut length = 0;
+    let r = tree.find_from_stream(&mut backtrace, &mut length).await;
+
+    (r.ok().map(|n| n.read().unwrap().data_ref().unwrap()), length)
+}
+
+#[tokio::test]
+async fn stream_lookup() {
+    let tree = tree();
+    assert_eq!(find_from_stream(&tree, b"content-type: a\r\n").await, (Some(1), 13));
+    assert_eq!(find_from_stream(&tree, b"X-Custom-Trace: 1\r\n").await, (Some(3), 15));
+    assert_eq!(find_from_stream(&tree, b"X-Other: 1\r\n").await, (Some(2), 8));
+    assert_eq!(find_from_stream(&tree, b"Accept: */*\r\n").await.0, None);
+}
@@ -0,0 +1,126 @@
+use tokio_httpserver::header::*;
+
+fn date(s: &str) -> Option<u64> {
+    HttpDate::parse_str(s).ok().map(|d| d.secs())
+}
+
+#[test]
+fn http_date_forms() {
+    assert_eq!(date("Sun, 06 Nov 1994 08:49:37 GMT"), Some(784111777));
+    assert_eq!(date("Sunday, 06-Nov-94 08:49:37 GMT"), Some(784111777));
+    assert_eq!(date("Sun Nov  6 08:49:37 1994"), Some(784111777));
+
+    let mut buf = Vec::new();
+    HttpDate::from_secs(784111777).write(&mut buf);
+    assert_eq!(buf, b"Sun, 06 Nov 1994 08:49:37 GMT");
+}
+
+#[test]
+fn http_date_rejects_out_of_range() {
+    assert_eq!(date("Sun, 06 Nov 99999999999999 08:49:37 GMT"), None);
+    assert_eq!(date("Sun, 06 Nov 10000 08:49:37 GMT"), None);
+    assert_eq!(date("Sun, 06 Nov 1969 08:49:37 GMT"), None);
+    assert_eq!(date("Sun, 06 Nov 1994 24:00:00 GMT"), None);
+    assert_eq!(date("Sun, 32 Nov 1994 08:49:37 GMT"), None);
+    assert_eq!(date("Sun, 06 Nov 1994 08:49:37 PST"), None);
+    assert!(date("Fri, 31 Dec 9999 23:59:59 GMT").is_some());
+}
+
+#[test]
+fn q_values() {
+    let list = accept::parse_quality_list("gzip;q=0.5, br, deflate;q=0, *;q=1.000").ok().unwrap();
+    let q: Vec<u16> = list.iter().map(|i| i.quality).collect();
+    assert_eq!(q, vec![500, 1000, 0, 1000]);
+
+    for bad in &["gzip;q=abc", "gzip;q=2", "gzip;q=1.5", "gzip;q=0.1234", "gzip;q="] {
+        assert!(accept::parse_quality_list(bad).is_err(), "{}", bad);
+    }
+
+    let mut buf = Vec::new();
+    accept::write_quality_list(&[QualityItem::new("br", 1000), QualityItem::new("gzip", 250)], &mut buf);
+    assert_eq!(buf, b"br, gzip;q=0.25");
+}
+
+#[test]
+fn basic_authorization_base64() {
+    match Authorization::parse(b"Basic QWxhZGRpbjpvcGVuIHNlc2FtZQ==").ok().unwrap() {
+        Authorization::Basic { username, password } => {
+            assert_eq!(username, "Aladdin");
+            assert_eq!(password, "open sesame");
+        },
+        _ => panic!("not basic")
+    }
+
+    // unpadded input decodes the same
+    assert!(Authorization::parse(b"Basic QWxhZGRpbjpvcGVuIHNlc2FtZQ").is_ok());
+
+    let basic = Authorization::Basic { username: "a".to_string(), password: "bc".to_string() };
+    assert_eq!(Authorization::parse(&basic.to_bytearray()).ok().unwrap(), basic);
+
+    for bad in &[&b"Basic QWxh*GRpbg=="[..], b"Basic QQ=", b"Basic YWJj", b"Bearer"] {
+        assert!(Authorization::parse(bad).is_err(), "{:?}", String::from_utf8_lossy(bad));
+    }
+}
+
+#[test]
+fn range_specs() {
+    let r = Range::parse(b"bytes=0-499, 500-, -200").ok().unwrap();
+    assert_eq!(r.specs(), &[ByteRangeSpec::FromTo(0, 499), ByteRangeSpec::From(500), ByteRangeSpec::Last(200)]);
+    assert_eq!(r.specs()[0].to_satisfiable(100), Some((0, 99)));
+    assert_eq!(r.specs()[1].to_satisfiable(100), None);
+    assert_eq!(r.specs()[2].to_satisfiable(100), Some((0, 99)));
+
+    for bad in &[&b"items=0-1"[..], b"bytes=5-1", b"bytes=a-b", b"bytes=", b"bytes=1", b"bytes=99999999999999999999-"] {
+        assert!(Range::parse(bad).is_err(), "{:?}", String::from_utf8_lossy(bad));
+    }
+
+    assert_eq!(Range::bytes(3, Some(7)).to_bytearray(), b"bytes=3-7");
+}
+
+#[test]
+fn entity_tags() {
+    let strong = EntityTag::parse(b"\"abc\"").ok().unwrap();
+    let weak = EntityTag::parse(b"W/\"abc\"").ok().unwrap();
+    assert!(!strong.weak && weak.weak);
+    assert!(strong.weak_eq(&weak));
+    assert!(!strong.strong_eq(&weak));
+    assert_eq!(weak.to_bytearray(), b"W/\"abc\"");
+
+    for bad in &[&b"abc"[..], b"\"a\"b\"", b"W/abc", b"\""] {
+        assert!(EntityTag::parse(bad).is_err(), "{:?}", String::from_utf8_lossy(bad));
+    }
+
+    match IfNoneMatch::parse(b"\"a\", W/\"b\"").ok().unwrap() {
+        IfNoneMatch::Tags(tags) => assert_eq!(tags.len(), 2),
+        IfNoneMatch::Any => panic!("not a list")
+    }
+    assert!(IfNoneMatch::parse(b"*").ok().unwrap() == IfNoneMatch::Any);
+}
+
+fn status<T>(r: tokio_httpserver::Result<T>) -> u16 {
+    match r {
+        Ok(_) => 200,
+        Err(e) => e.status()
+    }
+}
+
+#[test]
+fn malformed_client_headers_are_400() {
+    use tokio_httpserver::{Headers, HeaderValue};
+
+    let headers = |name: &str, value: &str| {
+        let mut h = Headers::default();
+        h.insert(name.as_bytes(), HeaderValue::ByteArray(value.as_bytes().to_vec()));
+        h
+    };
+
+    assert_eq!(status(negotiate::encoding(&headers("Accept-Encoding", "gzip;q=abc"), &["gzip"])), 400);
+    assert_eq!(status(negotiate::media_type(&headers("Accept", "text/html;q=2"), &["text/html"])), 400);
+    assert_eq!(status(negotiate::encoding(&headers("Accept-Encoding", "br;q=0"), &["br"])), 406);
+
+    assert_eq!(status(headers("Range", "bytes=x").typed::<Range>()), 400);
+    assert_eq!(status(headers("If-Modified-Since", "yesterday").typed::<IfModifiedSince>()), 400);
+    assert_eq!(status(headers("Content-Type", "text").typed::<ContentType>()), 400);
+    assert_eq!(status(headers("If-None-Match", "abc").typed::<IfNoneMatch>()), 400);
+    assert_eq!(status(headers("Authorization", "Basic !!").typed::<Authorization>()), 400);
+}
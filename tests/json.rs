@@ -0,0 +1,51 @@
+use std::collections::HashMap;
+
+use tokio_httpserver::*;
+use tokio_httpserver::header::{ContentType, TypedHeader};
+use tokio_httpserver::body::json;
+
+fn content_type(value: &str) -> ContentType {
+    ContentType::parse(value.as_bytes()).ok().unwrap()
+}
+
+#[test]
+fn decoder_fills_json_body() {
+    let v = json::decoder::<HashMap<String, u32>>(br#"{"a": 1, "b": 2}"#).ok().unwrap();
+    let body = Body::Json(v);
+
+    assert_eq!(body.json_ref::<HashMap<String, u32>>().unwrap()["b"], 2);
+    assert!(body.json_ref::<Vec<u32>>().is_none());
+
+    let map = body.into_json::<HashMap<String, u32>>().unwrap();
+    assert_eq!(map["a"], 1);
+}
+
+#[test]
+fn invalid_json_is_400() {
+    match json::decoder::<HashMap<String, u32>>(br#"{"a": "#) {
+        Err(e) => assert_eq!(e.status(), 400),
+        Ok(_) => panic!("truncated json accepted")
+    }
+
+    match json::decoder::<Vec<u32>>(br#"{"a": 1}"#) {
+        Err(e) => assert_eq!(e.status(), 400),
+        Ok(_) => panic!("object accepted as an array")
+    }
+}
+
+#[test]
+fn json_media_types() {
+    assert!(json::is_json(&content_type("application/json; charset=utf-8")));
+    assert!(json::is_json(&content_type("application/problem+json")));
+    assert!(!json::is_json(&content_type("text/plain")));
+}
+
+#[test]
+fn json_response_body() {
+    let content = ResponseContent::json(&vec![1, 2]).ok().unwrap();
+
+    match content.body {
+        Body::ByteArray(v) => assert_eq!(v, b"[1,2]"),
+        _ => panic!("json response is not a byte array")
+    }
+}
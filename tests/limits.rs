@@ -0,0 +1,76 @@
+use tokio_httpserver::*;
+use tokio_httpserver::stream_handler::body::BodyStream;
+use tokio_httpserver::stream_handler::framing::BodyFraming;
+
+mod common;
+
+fn limits() -> Limits {
+    Limits {
+        request_line: 64,
+        header_size: 64,
+        header_count: 4,
+        header_bytes: 160,
+        body_size: 16,
+        ..Default::default()
+    }
+}
+
+/// the status of reading the request line and headers of `payload`
+async fn head_status(payload: Vec<u8>) -> u16 {
+    let mut stream = common::stream(payload).await;
+    stream.set_limits(limits());
+
+    let r = match stream.read_request_line(ParseMode::Strict).await {
+        Ok(_) => stream.read_headers(ParseMode::Strict, &limits()).await.map(|_| ()),
+        Err(e) => Err(e)
+    };
+
+    common::status(r)
+}
+
+fn request(target_len: usize, headers: &[String]) -> Vec<u8> {
+    let mut r = format!("GET /{} HTTP/1.1\r\n", "a".repeat(target_len));
+    for h in headers {
+        r.push_str(h);
+        r.push_str("\r\n");
+    }
+    r.push_str("\r\n");
+    r.into_bytes()
+}
+
+#[tokio::test]
+async fn long_request_line_is_414() {
+    assert_eq!(head_status(request(40, &[])).await, 200);
+    assert_eq!(head_status(request(100, &[])).await, 414);
+    // no line end within the limit at all
+    assert_eq!(head_status(vec![b'a'; 1000]).await, 414);
+}
+
+#[tokio::test]
+async fn large_header_sections_are_431() {
+    let header = |len: usize| format!("X-A: {}", "v".repeat(len));
+
+    assert_eq!(head_status(request(1, &[header(50)])).await, 200);
+    // a single line
+    assert_eq!(head_status(request(1, &[header(100)])).await, 431);
+    // the number of lines
+    let many: Vec<String> = (0..5).map(|_| header(1)).collect();
+    assert_eq!(head_status(request(1, &many)).await, 431);
+    // all lines together
+    let big: Vec<String> = (0..4).map(|_| header(50)).collect();
+    assert_eq!(head_status(request(1, &big)).await, 431);
+}
+
+#[tokio::test]
+async fn large_bodies_are_413() {
+    let stream = std::sync::Arc::new(tokio::sync::Mutex::new(common::stream(vec![b'a'; 20]).await));
+    let r = BodyStream::new(stream, BodyFraming::ContentLength(20), ParseMode::Strict, limits());
+    assert_eq!(common::status(r), 413);
+
+    let payload = [b'a'; 20];
+    let mut body = common::chunked_body(&payload[..16], 8, limits()).await;
+    assert_eq!(body.read_to_end().await.ok().unwrap().len(), 16);
+
+    let mut body = common::chunked_body(&payload, 8, limits()).await;
+    assert_eq!(common::status(body.read_to_end().await), 413);
+}
@@ -0,0 +1,156 @@
+mod common;
+
+use tokio_httpserver::*;
+use tokio_httpserver::header::{ContentType, TypedHeader};
+use tokio_httpserver::body::{Multipart, MultipartLimits};
+
+fn content_type(value: &str) -> ContentType {
+    ContentType::parse(value.as_bytes()).ok().unwrap()
+}
+
+async fn multipart(ct: &str, payload: &[u8], limits: MultipartLimits) -> Result<Multipart> {
+    let body = common::chunked_body(payload, 7, Limits::default()).await;
+
+    Multipart::new(body, &content_type(ct), limits)
+}
+
+/// (name, filename, content type, data) of every part
+async fn collect(mut m: Multipart) -> Result<Vec<(Option<String>, Option<String>, Option<String>, Vec<u8>)>> {
+    let mut parts = Vec::new();
+
+    while let Some(mut part) = m.next_part().await? {
+        let data = part.read_to_end().await?;
+        parts.push((part.headers.name.clone(), part.headers.filename.clone()
+                    , part.headers.content_type.as_ref().map(|c| c.mime().to_string()), data));
+    }
+
+    Ok(parts)
+}
+
+#[tokio::test]
+async fn boundary_and_fields() {
+    let payload = b"preamble to drop\r\n\
+        --AaB03x\r\n\
+        Content-Disposition: form-data; name=\"a\"\r\n\
+        \r\n\
+        one\r\n\
+        --AaB03x  \r\n\
+        Content-Disposition: form-data; name=\"b\"\r\n\
+        \r\n\
+        two\r\n\
+        --AaB03x--\r\n\
+        epilogue";
+
+    let m = multipart("multipart/form-data; boundary=AaB03x", payload, MultipartLimits::default()).await.ok().unwrap();
+    let parts = collect(m).await.ok().unwrap();
+
+    assert_eq!(parts.len(), 2);
+    assert_eq!(parts[0].0.as_deref(), Some("a"));
+    assert_eq!(parts[0].3, b"one");
+    assert_eq!(parts[1].0.as_deref(), Some("b"));
+    assert_eq!(parts[1].3, b"two");
+}
+
+#[tokio::test]
+async fn quoted_boundary_without_preamble() {
+    let payload = b"--a b:c\r\n\
+        Content-Disposition: form-data; name=\"x\"\r\n\
+        \r\n\
+        value\r\n\
+        --a b:c--";
+
+    let m = multipart("multipart/form-data; boundary=\"a b:c\"", payload, MultipartLimits::default()).await.ok().unwrap();
+    let parts = collect(m).await.ok().unwrap();
+
+    assert_eq!(parts.len(), 1);
+    assert_eq!(parts[0].3, b"value");
+}
+
+#[tokio::test]
+async fn invalid_boundary() {
+    let limits = MultipartLimits::default();
+
+    assert_eq!(common::status(multipart("multipart/form-data", b"", limits).await), 400);
+    assert_eq!(common::status(multipart(&format!("multipart/form-data; boundary={}", "x".repeat(71))
+                                        , b"", limits).await), 400);
+    assert_eq!(common::status(multipart("text/plain; boundary=x", b"", limits).await), 415);
+}
+
+#[tokio::test]
+async fn filename_part() {
+    let payload = b"--xyz\r\n\
+        Content-Disposition: form-data; name=\"upload\"; filename=\"notes.txt\"\r\n\
+        Content-Type: text/plain\r\n\
+        \r\n\
+        file contents\r\n\
+        --xyz--\r\n";
+
+    let m = multipart("multipart/form-data; boundary=xyz", payload, MultipartLimits::default()).await.ok().unwrap();
+    let parts = collect(m).await.ok().unwrap();
+
+    assert_eq!(parts.len(), 1);
+    assert_eq!(parts[0].0.as_deref(), Some("upload"));
+    assert_eq!(parts[0].1.as_deref(), Some("notes.txt"));
+    assert_eq!(parts[0].2.as_deref(), Some("text/plain"));
+    assert_eq!(parts[0].3, b"file contents");
+}
+
+#[tokio::test]
+async fn crlf_inside_content() {
+    // CRLF, blank lines and a shorter dash line are all content
+    let content = b"line one\r\nline two\r\n\r\n--xy\r\n-";
+    let mut payload = b"--xyz\r\nContent-Disposition: form-data; name=\"t\"\r\n\r\n".to_vec();
+    payload.extend_from_slice(content);
+    payload.extend_from_slice(b"\r\n--xyz--\r\n");
+
+    let m = multipart("multipart/form-data; boundary=xyz", &payload, MultipartLimits::default()).await.ok().unwrap();
+    let parts = collect(m).await.ok().unwrap();
+
+    assert_eq!(parts.len(), 1);
+    assert_eq!(parts[0].3, content);
+}
+
+#[tokio::test]
+async fn missing_close_delimiter() {
+    let limits = MultipartLimits::default();
+
+    let ended_in_part = b"--xyz\r\nContent-Disposition: form-data; name=\"a\"\r\n\r\nno end";
+    let m = multipart("multipart/form-data; boundary=xyz", ended_in_part, limits).await.ok().unwrap();
+    assert_eq!(common::status(collect(m).await), 400);
+
+    let no_delimiter = b"just some bytes";
+    let m = multipart("multipart/form-data; boundary=xyz", no_delimiter, limits).await.ok().unwrap();
+    assert_eq!(common::status(collect(m).await), 400);
+}
+
+#[tokio::test]
+async fn part_size_limit() {
+    let limits = MultipartLimits { max_part_size: 16, ..MultipartLimits::default() };
+
+    let mut payload = b"--xyz\r\nContent-Disposition: form-data; name=\"small\"\r\n\r\n".to_vec();
+    payload.extend_from_slice(&[b'a'; 16]);
+    payload.extend_from_slice(b"\r\n--xyz\r\nContent-Disposition: form-data; name=\"big\"\r\n\r\n");
+    payload.extend_from_slice(&[b'b'; 17]);
+    payload.extend_from_slice(b"\r\n--xyz--\r\n");
+
+    let mut m = multipart("multipart/form-data; boundary=xyz", &payload, limits).await.ok().unwrap();
+
+    let mut small = m.next_part().await.ok().unwrap().unwrap();
+    assert_eq!(small.read_to_end().await.ok().unwrap().len(), 16);
+
+    let mut big = m.next_part().await.ok().unwrap().unwrap();
+    assert_eq!(common::status(big.read_to_end().await), 413);
+}
+
+#[tokio::test]
+async fn malformed_part_headers_are_400() {
+    let limits = MultipartLimits::default();
+
+    let bad_type = b"--xyz\r\nContent-Type: text\r\n\r\ndata\r\n--xyz--\r\n";
+    let m = multipart("multipart/form-data; boundary=xyz", bad_type, limits).await.ok().unwrap();
+    assert_eq!(common::status(collect(m).await), 400);
+
+    let bad_param = b"--xyz\r\nContent-Disposition: form-data; name\r\n\r\ndata\r\n--xyz--\r\n";
+    let m = multipart("multipart/form-data; boundary=xyz", bad_param, limits).await.ok().unwrap();
+    assert_eq!(common::status(collect(m).await), 400);
+}
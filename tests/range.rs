@@ -0,0 +1,91 @@
+use tokio_httpserver::*;
+use tokio_httpserver::response::{range, ResponseBuilder, FileBody};
+
+mod common;
+
+const DATA: &[u8] = b"0123456789abcdefghij";
+
+fn content() -> ResponseContent {
+    ResponseBuilder::new().bytes(DATA.to_vec()).etag_from_body().build().ok().unwrap()
+}
+
+fn header<'a>(content: &'a ResponseContent, name: &str) -> &'a str {
+    std::str::from_utf8(content.headers.get(name).unwrap()).unwrap()
+}
+
+#[test]
+fn single_range_is_partial_content() {
+    let request = common::get(&[("Range", "bytes=2-5")]);
+    let out = range::apply(&request, content()).ok().unwrap();
+
+    assert_eq!(out.captial.status().as_u16(), 206);
+    assert_eq!(header(&out, "Content-Range"), "bytes 2-5/20");
+    match out.body {
+        Body::ByteArray(v) => assert_eq!(v, b"2345"),
+        _ => panic!("range not sliced")
+    }
+}
+
+#[tokio::test]
+async fn several_ranges_are_multipart_byteranges() {
+    let p = common::temp_file("ranges", DATA);
+    let file = FileBody::open(&p).await.ok().unwrap();
+    let content = ResponseBuilder::new().body(Body::File(file)).build().ok().unwrap();
+
+    let request = common::get(&[("Range", "bytes=0-1,-3")]);
+    let out = range::apply(&request, content).ok().unwrap();
+    assert_eq!(out.captial.status().as_u16(), 206);
+
+    let ct = header(&out, "Content-Type").to_string();
+    let boundary = ct.strip_prefix("multipart/byteranges; boundary=").unwrap().to_string();
+
+    let (r, wire) = common::send(out).await;
+    assert!(r.is_ok());
+
+    let body = common::dechunk(common::body_of(&wire)).unwrap();
+    let body = String::from_utf8(body).unwrap();
+    assert!(body.contains("Content-Range: bytes 0-1/20\r\n\r\n01\r\n"));
+    assert!(body.contains("Content-Range: bytes 17-19/20\r\n\r\nhij\r\n"));
+    assert!(body.ends_with(&format!("--{}--\r\n", boundary)));
+
+    std::fs::remove_file(&p).unwrap();
+}
+
+#[test]
+fn unsatisfiable_range_is_416() {
+    let request = common::get(&[("Range", "bytes=30-40")]);
+    let out = range::apply(&request, content()).ok().unwrap();
+
+    assert_eq!(out.captial.status().as_u16(), 416);
+    assert_eq!(header(&out, "Content-Range"), "bytes */20");
+}
+
+#[test]
+fn stale_if_range_sends_the_whole_body() {
+    let request = common::get(&[("Range", "bytes=2-5"), ("If-Range", "\"stale\"")]);
+    let out = range::apply(&request, content()).ok().unwrap();
+
+    assert_eq!(out.captial.status().as_u16(), 200);
+    assert!(out.headers.get("Content-Range").is_none());
+    match out.body {
+        Body::ByteArray(v) => assert_eq!(v, DATA),
+        _ => panic!("body changed")
+    }
+}
+
+#[tokio::test]
+async fn file_ending_early_aborts_the_body() {
+    let p = common::temp_file("short-ranges", DATA);
+    let file = tokio::fs::File::open(&p).await.unwrap();
+    // the file is shorter than the length it was opened with
+    let content = ResponseBuilder::new().body(Body::File(FileBody::new(file, 0, 40))).build().ok().unwrap();
+
+    let request = common::get(&[("Range", "bytes=0-1,30-35")]);
+    let out = range::apply(&request, content).ok().unwrap();
+
+    let (r, wire) = common::send(out).await;
+    assert!(r.is_err());
+    assert!(common::dechunk(common::body_of(&wire)).is_none());
+
+    std::fs::remove_file(&p).unwrap();
+}
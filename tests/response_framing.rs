@@ -0,0 +1,127 @@
+mod common;
+
+use std::sync::Arc;
+
+use tokio::sync::{RwLock, mpsc};
+
+use tokio_httpserver::*;
+use tokio_httpserver::header::HttpDate;
+use tokio_httpserver::response::{ResponseBuilder, StatusCode};
+
+fn head_of(wire: &[u8]) -> String {
+    let i = wire.windows(4).position(|w| w == b"\r\n\r\n").unwrap();
+    String::from_utf8(wire[..i + 2].to_vec()).unwrap()
+}
+
+/// the value of the only `name` field in the head
+fn field<'a>(head: &'a str, name: &str) -> Option<&'a str> {
+    let mut values = head.split("\r\n").skip(1).filter_map(|line| {
+        let i = line.find(':')?;
+        if line[..i].eq_ignore_ascii_case(name) { Some(line[i + 1..].trim()) } else { None }
+    });
+
+    let value = values.next();
+    assert!(values.next().is_none(), "{} sent twice", name);
+    value
+}
+
+fn channel(chunks: &[&[u8]]) -> Body {
+    let (tx, rx) = mpsc::channel(chunks.len() + 1);
+    for chunk in chunks {
+        tx.try_send(Ok(chunk.to_vec())).ok().unwrap();
+    }
+    Body::Channel(rx)
+}
+
+#[tokio::test]
+async fn date_and_server() {
+    let (r, wire) = common::send(ResponseBuilder::new().text("hi").build().ok().unwrap()).await;
+    r.ok().unwrap();
+    let head = head_of(&wire);
+
+    assert_eq!(field(&head, "Server"), Some("tokio-httpserver"));
+    let date = HttpDate::parse_str(field(&head, "Date").unwrap()).ok().unwrap();
+    assert!(HttpDate::now().secs() - date.secs() < 5);
+
+    // handler values win
+    let content = ResponseBuilder::new().header("Server", "custom")
+        .header("Date", "Sun, 06 Nov 1994 08:49:37 GMT").build().ok().unwrap();
+    let (_, wire) = common::send(content).await;
+    let head = head_of(&wire);
+    assert_eq!(field(&head, "Server"), Some("custom"));
+    assert_eq!(field(&head, "Date"), Some("Sun, 06 Nov 1994 08:49:37 GMT"));
+
+    // and both can be turned off
+    let options = ResponseOptions { server: None, date: false };
+    let writer = Arc::new(RwLock::new(Vec::new()));
+    Response::new(writer.clone(), ResponseContent::default())
+        .with_options(Arc::new(options)).send().await.ok().unwrap();
+    let head = head_of(&writer.read().await);
+    assert_eq!(field(&head, "Server"), None);
+    assert_eq!(field(&head, "Date"), None);
+}
+
+#[tokio::test]
+async fn content_length_replaces_handler_framing() {
+    let content = ResponseBuilder::new().header("Content-Length", "99")
+        .header("Transfer-Encoding", "chunked").text("hello").build().ok().unwrap();
+    let (_, wire) = common::send(content).await;
+    let head = head_of(&wire);
+
+    assert_eq!(field(&head, "Content-Length"), Some("5"));
+    assert_eq!(field(&head, "Transfer-Encoding"), None);
+    assert_eq!(common::body_of(&wire), b"hello");
+}
+
+#[tokio::test]
+async fn unknown_length_is_chunked_on_http11() {
+    let content = ResponseBuilder::new().body(channel(&[b"ab", b"", b"cde"])).build().ok().unwrap();
+    let (r, wire) = common::send(content).await;
+    r.ok().unwrap();
+    let head = head_of(&wire);
+
+    assert_eq!(field(&head, "Transfer-Encoding"), Some("chunked"));
+    assert_eq!(field(&head, "Content-Length"), None);
+    assert_eq!(common::dechunk(common::body_of(&wire)).unwrap(), b"abcde");
+}
+
+#[tokio::test]
+async fn unknown_length_closes_on_http10() {
+    let mut content = ResponseBuilder::new().body(channel(&[b"ab", b"cde"])).build().ok().unwrap();
+    content.captial.set_version(b"HTTP/1.0");
+    let (r, wire) = common::send(content).await;
+    r.ok().unwrap();
+    let head = head_of(&wire);
+
+    assert!(head.starts_with("HTTP/1.0 200 OK\r\n"));
+    assert_eq!(field(&head, "Connection"), Some("close"));
+    assert_eq!(field(&head, "Transfer-Encoding"), None);
+    assert_eq!(field(&head, "Content-Length"), None);
+    assert_eq!(common::body_of(&wire), b"abcde");
+}
+
+#[tokio::test]
+async fn no_body_on_204_and_304() {
+    for status in [StatusCode::NO_CONTENT, StatusCode::NOT_MODIFIED].iter() {
+        let content = ResponseBuilder::new().status(*status).header("Content-Length", "4")
+            .text("body").build().ok().unwrap();
+        let (r, wire) = common::send(content).await;
+        r.ok().unwrap();
+        let head = head_of(&wire);
+
+        assert_eq!(field(&head, "Content-Length"), None, "{:?}", status);
+        assert_eq!(field(&head, "Transfer-Encoding"), None, "{:?}", status);
+        assert!(common::body_of(&wire).is_empty(), "{:?}", status);
+    }
+}
+
+#[tokio::test]
+async fn head_keeps_length_without_body() {
+    let writer = Arc::new(RwLock::new(Vec::new()));
+    let content = ResponseBuilder::new().text("hello").build().ok().unwrap();
+    Response::new(writer.clone(), content).head_only(true).send().await.ok().unwrap();
+
+    let wire = writer.read().await;
+    assert_eq!(field(&head_of(&wire), "Content-Length"), Some("5"));
+    assert!(common::body_of(&wire).is_empty());
+}
@@ -0,0 +1,59 @@
+use tokio_httpserver::header::ContentType;
+use tokio_httpserver::response::ResponseHeaders;
+
+fn written(h: &ResponseHeaders) -> String {
+    let mut buf = Vec::new();
+    h.write(&mut buf);
+    String::from_utf8(buf).unwrap()
+}
+
+#[test]
+fn rejects_field_splitting() {
+    let mut h = ResponseHeaders::new();
+
+    for value in [&b"a\r\nSet-Cookie: x=1"[..], b"a\nb", b"a\rb", b"a\0b", b"a\x7fb"].iter() {
+        match h.set("X-Test", value) {
+            Err(e) => assert_eq!(e.status(), 500),
+            Ok(_) => panic!("{:?} accepted", value)
+        }
+    }
+
+    for name in ["", "X Test", "X-Test:", "X\r\nTest"].iter() {
+        assert!(h.append(name, b"v").is_err(), "{:?}", name);
+    }
+
+    // HTAB, SP and obs-text are allowed
+    h.set("X-Test", b"a\tb c\xe9").ok().unwrap();
+    assert!(!h.is_empty() && h.len() == 1);
+}
+
+#[test]
+fn set_append_and_single_valued() {
+    let mut h = ResponseHeaders::new();
+
+    h.append("Set-Cookie", b"a=1").ok().unwrap();
+    h.append("set-cookie", b"b=2").ok().unwrap();
+    assert_eq!(h.get_all("Set-Cookie").count(), 2);
+
+    h.append_typed(&ContentType::new("text/plain")).ok().unwrap();
+    h.append("content-type", b"text/html").ok().unwrap();
+    assert_eq!(h.get_all("Content-Type").collect::<Vec<_>>(), vec![&b"text/html"[..]]);
+
+    h.set("Set-Cookie", b"c=3").ok().unwrap();
+    assert_eq!(h.get_all("set-cookie").collect::<Vec<_>>(), vec![&b"c=3"[..]]);
+
+    assert!(h.remove("SET-COOKIE"));
+    assert!(!h.contains("Set-Cookie"));
+    assert_eq!(written(&h), "Content-Type: text/html\r\n");
+}
+
+#[test]
+fn unchecked_fields_follow_checked_ones() {
+    let mut h = ResponseHeaders::new();
+
+    h.unchecked_mut().push(b"X-Raw", b"1");
+    assert!(!h.is_empty());
+
+    h.set("Server", b"test").ok().unwrap();
+    assert_eq!(written(&h), "Server: test\r\nX-Raw: 1\r\n");
+}
@@ -0,0 +1,54 @@
+mod common;
+
+use std::sync::Arc;
+
+use tokio::io::AsyncReadExt;
+use tokio::sync::RwLock;
+
+use tokio_httpserver::*;
+use tokio_httpserver::response::{FileBody, ResponseBuilder};
+
+fn pattern(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i % 251) as u8).collect()
+}
+
+#[tokio::test]
+async fn file_slice_over_tcp() {
+    let data = pattern(300_000);
+    let path = common::temp_file("sendfile", &data);
+
+    let file = FileBody::open(&path).await.ok().unwrap().slice(1000, 250_000);
+    assert_eq!((file.offset(), file.len()), (1000, 250_000));
+
+    let (server, mut client) = common::tcp_pair().await;
+    let reader = tokio::spawn(async move {
+        let mut wire = Vec::new();
+        client.read_to_end(&mut wire).await.unwrap();
+        wire
+    });
+
+    let content = ResponseBuilder::new().body(Body::File(file)).build().ok().unwrap();
+    let writer = Arc::new(RwLock::new(server));
+    Response::new(writer.clone(), content).send().await.ok().unwrap();
+    drop(writer);
+
+    let wire = reader.await.unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let body = common::body_of(&wire);
+    assert_eq!(body.len(), 250_000);
+    assert!(body == &data[1000..251_000]);
+}
+
+#[tokio::test]
+async fn slice_is_clamped_to_the_file() {
+    let path = common::temp_file("sendfile-clamp", &pattern(100));
+
+    let file = FileBody::open(&path).await.ok().unwrap().slice(40, 1000).slice(50, 1000);
+    assert_eq!((file.offset(), file.len()), (90, 10));
+
+    let file = FileBody::open(&path).await.ok().unwrap().slice(200, 10);
+    assert!(file.is_empty());
+
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,134 @@
+use tokio_httpserver::*;
+use tokio_httpserver::stream_handler::framing::{self, BodyFraming};
+
+mod common;
+
+enum Expect {
+    Framing(BodyFraming),
+    Status(u16)
+}
+
+const CORPUS: &[(&str, ParseMode, &[u8], Expect)] = &[
+    ("content-length", ParseMode::Strict,
+     b"POST / HTTP/1.1\r\nHost: a\r\nContent-Length: 5\r\n\r\nhello",
+     Expect::Framing(BodyFraming::ContentLength(5))),
+    ("chunked", ParseMode::Strict,
+     b"POST / HTTP/1.1\r\nHost: a\r\nTransfer-Encoding: chunked\r\n\r\n0\r\n\r\n",
+     Expect::Framing(BodyFraming::Chunked)),
+    ("no body", ParseMode::Strict,
+     b"GET / HTTP/1.1\r\nHost: a\r\n\r\n",
+     Expect::Framing(BodyFraming::Empty)),
+    ("CL.TE", ParseMode::Strict,
+     b"POST / HTTP/1.1\r\nHost: a\r\nContent-Length: 13\r\nTransfer-Encoding: chunked\r\n\r\n0\r\n\r\nSMUGGLED",
+     Expect::Status(400)),
+    ("TE.CL", ParseMode::Strict,
+     b"POST / HTTP/1.1\r\nHost: a\r\nTransfer-Encoding: chunked\r\nContent-Length: 3\r\n\r\n8\r\nSMUGGLED\r\n0\r\n\r\n",
+     Expect::Status(400)),
+    ("TE.CL lenient", ParseMode::Lenient,
+     b"POST / HTTP/1.1\r\nHost: a\r\nTransfer-Encoding: chunked\r\nContent-Length: 3\r\n\r\n",
+     Expect::Status(400)),
+    ("duplicate CL, same value", ParseMode::Strict,
+     b"POST / HTTP/1.1\r\nHost: a\r\nContent-Length: 5\r\nContent-Length: 5\r\n\r\nhello",
+     Expect::Status(400)),
+    ("duplicate CL, conflicting", ParseMode::Strict,
+     b"POST / HTTP/1.1\r\nHost: a\r\nContent-Length: 5\r\nContent-Length: 6\r\n\r\nhello",
+     Expect::Status(400)),
+    ("CL list", ParseMode::Strict,
+     b"POST / HTTP/1.1\r\nHost: a\r\nContent-Length: 5, 5\r\n\r\nhello",
+     Expect::Status(400)),
+    ("CL with sign", ParseMode::Strict,
+     b"POST / HTTP/1.1\r\nHost: a\r\nContent-Length: +5\r\n\r\nhello",
+     Expect::Status(400)),
+    ("CL hex", ParseMode::Strict,
+     b"POST / HTTP/1.1\r\nHost: a\r\nContent-Length: 0x5\r\n\r\nhello",
+     Expect::Status(400)),
+    ("CL overflow", ParseMode::Strict,
+     b"POST / HTTP/1.1\r\nHost: a\r\nContent-Length: 99999999999999999999999999\r\n\r\n",
+     Expect::Status(400)),
+    ("TE space before colon", ParseMode::Strict,
+     b"POST / HTTP/1.1\r\nHost: a\r\nTransfer-Encoding : chunked\r\nContent-Length: 3\r\n\r\n",
+     Expect::Status(400)),
+    ("TE space before colon, lenient still sees TE", ParseMode::Lenient,
+     b"POST / HTTP/1.1\r\nHost: a\r\nTransfer-Encoding : chunked\r\nContent-Length: 3\r\n\r\n",
+     Expect::Status(400)),
+    ("TE obs-fold", ParseMode::Strict,
+     b"POST / HTTP/1.1\r\nHost: a\r\nTransfer-Encoding:\r\n chunked\r\nContent-Length: 3\r\n\r\n",
+     Expect::Status(400)),
+    ("TE xchunked", ParseMode::Strict,
+     b"POST / HTTP/1.1\r\nHost: a\r\nTransfer-Encoding: xchunked\r\n\r\n",
+     Expect::Status(400)),
+    ("TE chunked not final", ParseMode::Strict,
+     b"POST / HTTP/1.1\r\nHost: a\r\nTransfer-Encoding: chunked, identity\r\n\r\n",
+     Expect::Status(400)),
+    ("TE chunked twice, one line", ParseMode::Strict,
+     b"POST / HTTP/1.1\r\nHost: a\r\nTransfer-Encoding: chunked, chunked\r\n\r\n",
+     Expect::Status(400)),
+    ("TE chunked twice, two lines", ParseMode::Strict,
+     b"POST / HTTP/1.1\r\nHost: a\r\nTransfer-Encoding: chunked\r\nTransfer-Encoding: chunked\r\n\r\n",
+     Expect::Status(400)),
+    ("TE split across lines, chunked not final", ParseMode::Strict,
+     b"POST / HTTP/1.1\r\nHost: a\r\nTransfer-Encoding: chunked\r\nTransfer-Encoding: identity\r\n\r\n",
+     Expect::Status(400)),
+    ("TE empty element", ParseMode::Strict,
+     b"POST / HTTP/1.1\r\nHost: a\r\nTransfer-Encoding: ,chunked\r\n\r\n",
+     Expect::Status(400)),
+    ("TE with parameters", ParseMode::Strict,
+     b"POST / HTTP/1.1\r\nHost: a\r\nTransfer-Encoding: chunked;a=b\r\n\r\n",
+     Expect::Status(400)),
+    ("TE unknown coding before chunked", ParseMode::Strict,
+     b"POST / HTTP/1.1\r\nHost: a\r\nTransfer-Encoding: gzip, chunked\r\n\r\n",
+     Expect::Status(501)),
+    ("TE in HTTP/1.0", ParseMode::Strict,
+     b"POST / HTTP/1.0\r\nHost: a\r\nTransfer-Encoding: chunked\r\n\r\n",
+     Expect::Status(400)),
+    ("bare LF", ParseMode::Strict,
+     b"POST / HTTP/1.1\r\nHost: a\nTransfer-Encoding: chunked\r\n\r\n",
+     Expect::Status(400)),
+    ("bare CR", ParseMode::Strict,
+     b"POST / HTTP/1.1\r\nHost: a\rTransfer-Encoding: chunked\r\n\r\n",
+     Expect::Status(400)),
+    ("NUL in TE value", ParseMode::Lenient,
+     b"POST / HTTP/1.1\r\nHost: a\r\nTransfer-Encoding: chunked\x00\r\n\r\n",
+     Expect::Status(400)),
+    ("vertical tab in TE value", ParseMode::Strict,
+     b"POST / HTTP/1.1\r\nHost: a\r\nTransfer-Encoding:\x0bchunked\r\n\r\n",
+     Expect::Status(400)),
+    ("TE tab separated", ParseMode::Strict,
+     b"POST / HTTP/1.1\r\nHost: a\r\nTransfer-Encoding:\tchunked\r\n\r\n",
+     Expect::Framing(BodyFraming::Chunked)),
+    ("TE mixed case", ParseMode::Strict,
+     b"POST / HTTP/1.1\r\nHost: a\r\ntRaNsFeR-eNcOdInG: ChUnKeD\r\n\r\n",
+     Expect::Framing(BodyFraming::Chunked)),
+];
+
+async fn parse(payload: &[u8], mode: ParseMode) -> Result<BodyFraming> {
+    let mut stream = common::stream(payload.to_vec()).await;
+
+    let line = stream.read_request_line(mode).await?;
+    let limits = *stream.limits_ref();
+    let headers = stream.read_headers(mode, &limits).await?;
+
+    framing::body_framing(&line.version, &headers)
+}
+
+#[tokio::test]
+async fn smuggling_corpus() {
+    for (name, mode, payload, expect) in CORPUS {
+        let r = parse(payload, *mode).await;
+
+        match (expect, r) {
+            (Expect::Framing(f), Ok(got)) => {
+                assert_eq!(*f, got, "{}", name);
+            },
+            (Expect::Status(s), Err(err)) => {
+                assert_eq!(*s, err.status(), "{}", name);
+            },
+            (Expect::Framing(f), Err(err)) => {
+                panic!("{}: expected {:?}, got error status {}", name, f, err.status());
+            },
+            (Expect::Status(s), Ok(got)) => {
+                panic!("{}: expected status {}, got {:?}", name, s, got);
+            }
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use tokio_httpserver::stream_handler::spool::{self, SpoolBody};
+
+mod common;
+
+async fn spool_body(payload: &[u8], threshold: usize, dir: &std::path::Path) -> SpoolBody {
+    let mut body = common::fixed_body(payload.to_vec()).await;
+
+    spool::spool(&mut body, threshold, dir).await.ok().unwrap()
+}
+
+#[tokio::test]
+async fn spool_file_in_configured_dir() {
+    let dir = std::env::temp_dir().join(format!("tokio_httpserver-spool-test-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    match spool_body(b"hello", 16, &dir).await {
+        SpoolBody::Memory(v) => assert_eq!(v, b"hello"),
+        SpoolBody::File(_) => panic!("small body spooled to disk")
+    }
+
+    let temp = match spool_body(b"a body larger than the threshold", 4, &dir).await {
+        SpoolBody::File(f) => f,
+        SpoolBody::Memory(_) => panic!("large body kept in memory")
+    };
+
+    let path = temp.path().to_path_buf();
+    assert_eq!(path.parent(), Some(dir.as_path()));
+    assert_eq!(std::fs::read(&path).unwrap(), b"a body larger than the threshold");
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    drop(temp);
+    for _ in 0..100 {
+        if !path.exists() {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    assert!(!path.exists());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
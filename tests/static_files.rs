@@ -0,0 +1,366 @@
+mod common;
+
+use tokio_httpserver::*;
+use std::sync::{Arc, Mutex};
+use tokio_httpserver::header::{HttpDate, TypedHeader};
+use tokio_httpserver::static_files::{StaticFiles, StaticOptions, AssetCache, CacheOptions, FileMeta, listing};
+use tokio_httpserver::static_files;
+
+fn message(err: &Error) -> String {
+    match err {
+        Error::Content(s) => s.clone(),
+        Error::Detail(_, s) => s.clone(),
+        Error::Simple(kind) => format!("{:?}", kind)
+    }
+}
+
+static REPORTED: Mutex<Vec<(String, std::path::PathBuf, std::io::ErrorKind)>> = Mutex::new(Vec::new());
+
+fn record(action: &str, p: &std::path::Path, e: &std::io::Error) {
+    REPORTED.lock().unwrap().push((action.to_string(), p.to_path_buf(), e.kind()));
+}
+
+#[tokio::test]
+async fn file_system_errors_do_not_carry_paths() {
+    let dir = std::env::temp_dir().join(format!("tokio_httpserver-missing-{}", std::process::id()));
+
+    static_files::set_error_hook(Some(record));
+
+    let err = listing::render(&dir, "/", false).await.err().unwrap();
+    assert_eq!(err.status(), 500);
+    assert!(!message(&err).contains(dir.to_str().unwrap()));
+
+    // the hook still gets the path
+    let reported = REPORTED.lock().unwrap();
+    assert!(reported.iter().any(|(action, p, kind)| {
+        action == "list" && *p == dir && *kind == std::io::ErrorKind::NotFound
+    }));
+}
+
+#[tokio::test]
+async fn missing_file_names_only_the_request_path() {
+    let root = std::env::temp_dir().join(format!("tokio_httpserver-static-{}", std::process::id()));
+    std::fs::create_dir_all(&root).unwrap();
+
+    let files = StaticFiles::new(&root, StaticOptions::default());
+    let request = RequestHeader {
+        method: Method::Get,
+        ..Default::default()
+    };
+
+    let err = files.serve(&request, b"/nope.txt").await.err().unwrap();
+    assert_eq!(err.status(), 404);
+    assert!(message(&err).contains("/nope.txt"));
+    assert!(!message(&err).contains(root.to_str().unwrap()));
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+fn scratch_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("tokio_httpserver-{}-{}", name, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[tokio::test]
+async fn cache_evicts_least_recently_used() {
+    let dir = scratch_dir("lru");
+    for name in &["a", "b", "c"] {
+        std::fs::write(dir.join(name), name.as_bytes()).unwrap();
+    }
+
+    let cache = AssetCache::new(CacheOptions {
+        max_entries: 2,
+        ..Default::default()
+    });
+
+    let a = cache.stat(&dir.join("a")).await.ok().unwrap().unwrap();
+    cache.stat(&dir.join("b")).await.ok().unwrap();
+    let data = cache.read(&dir.join("a"), &a).await.ok().unwrap().unwrap();
+    cache.stat(&dir.join("c")).await.ok().unwrap();
+    assert_eq!(cache.len(), 2);
+
+    // "b" was the oldest, "a" keeps its contents
+    let again = cache.read(&dir.join("a"), &a).await.ok().unwrap().unwrap();
+    assert!(Arc::ptr_eq(&data, &again));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn cache_hit_shares_the_buffer() {
+    let dir = scratch_dir("shared");
+    std::fs::write(dir.join("index.txt"), b"hello").unwrap();
+
+    let files = StaticFiles::new(&dir, StaticOptions {
+        cache: Some(CacheOptions::default()),
+        ..Default::default()
+    });
+    let request = RequestHeader {
+        method: Method::Get,
+        ..Default::default()
+    };
+
+    let mut buffers = Vec::new();
+    for _ in 0..2 {
+        let content = files.serve(&request, b"/index.txt").await.ok().unwrap();
+        match content.body {
+            Body::Shared(v) => buffers.push(v),
+            _ => panic!("cached file not shared")
+        }
+    }
+
+    assert_eq!(&buffers[0][..], b"hello");
+    assert!(Arc::ptr_eq(&buffers[0], &buffers[1]));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+/// index.html, docs/a.txt, docs/sub/ and .secret
+fn site(name: &str) -> std::path::PathBuf {
+    let root = scratch_dir(name);
+    std::fs::create_dir_all(root.join("docs").join("sub")).unwrap();
+    std::fs::write(root.join("index.html"), b"<h1>home</h1>").unwrap();
+    std::fs::write(root.join("docs").join("a.txt"), b"alpha").unwrap();
+    std::fs::write(root.join(".secret"), b"x").unwrap();
+    root
+}
+
+fn header<'a>(content: &'a ResponseContent, name: &str) -> Option<&'a str> {
+    content.headers.get(name).map(|v| std::str::from_utf8(v).unwrap())
+}
+
+async fn get(files: &StaticFiles, target: &str) -> Result<ResponseContent> {
+    files.serve(&common::get(&[]), target.as_bytes()).await
+}
+
+async fn body(content: ResponseContent) -> Vec<u8> {
+    let (r, wire) = common::send(content).await;
+    r.ok().unwrap();
+    common::body_of(&wire).to_vec()
+}
+
+#[tokio::test]
+async fn index_file_and_directory_redirect() {
+    let root = site("index");
+    let files = StaticFiles::new(&root, StaticOptions::default());
+
+    let content = get(&files, "/").await.ok().unwrap();
+    assert_eq!(header(&content, "Content-Type"), Some("text/html; charset=utf-8"));
+    assert_eq!(body(content).await, b"<h1>home</h1>");
+
+    let content = get(&files, "/docs/a.txt").await.ok().unwrap();
+    assert_eq!(header(&content, "Content-Type"), Some("text/plain; charset=utf-8"));
+    assert_eq!(body(content).await, b"alpha");
+
+    let content = get(&files, "/docs").await.ok().unwrap();
+    assert_eq!(content.captial.status().as_u16(), 301);
+    assert_eq!(header(&content, "Location"), Some("/docs/"));
+
+    // no index file and no listing
+    assert_eq!(common::status(get(&files, "/docs/").await), 403);
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[tokio::test]
+async fn directory_listing() {
+    let root = site("listing");
+    let files = StaticFiles::new(&root, StaticOptions {
+        listing: true,
+        ..Default::default()
+    });
+
+    let content = get(&files, "/docs/").await.ok().unwrap();
+    assert_eq!(header(&content, "Content-Type"), Some("text/html; charset=utf-8"));
+    let html = String::from_utf8(body(content).await).unwrap();
+    assert!(html.contains("<a href=\"../\">"));
+    assert!(html.contains("<a href=\"./sub/\">sub/</a>"));
+    assert!(html.contains("<a href=\"./a.txt\">a.txt</a>"));
+    assert!(html.find("sub/").unwrap() < html.find("a.txt").unwrap());
+
+    let html = listing::render(&root, "/", false).await.ok().unwrap();
+    assert!(!html.contains(".secret"));
+    let html = listing::render(&root, "/", true).await.ok().unwrap();
+    assert!(html.contains(".secret"));
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[tokio::test]
+async fn spa_fallback() {
+    let root = site("spa");
+
+    let files = StaticFiles::new(&root, StaticOptions::default());
+    assert_eq!(common::status(get(&files, "/app/route").await), 404);
+
+    let files = StaticFiles::new(&root, StaticOptions {
+        spa_fallback: true,
+        ..Default::default()
+    });
+    let content = get(&files, "/app/route").await.ok().unwrap();
+    assert_eq!(content.captial.status().as_u16(), 200);
+    assert_eq!(body(content).await, b"<h1>home</h1>");
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[tokio::test]
+async fn dot_files_are_404() {
+    let root = site("hidden");
+
+    let files = StaticFiles::new(&root, StaticOptions {
+        spa_fallback: true,
+        ..Default::default()
+    });
+    assert_eq!(common::status(get(&files, "/.secret").await), 404);
+    assert_eq!(common::status(get(&files, "/docs/%2esecret").await), 404);
+
+    let files = StaticFiles::new(&root, StaticOptions {
+        hidden: true,
+        ..Default::default()
+    });
+    assert_eq!(body(get(&files, "/.secret").await.ok().unwrap()).await, b"x");
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[tokio::test]
+async fn validators_and_conditional_get() {
+    let root = site("validators");
+    let files = StaticFiles::new(&root, StaticOptions::default());
+
+    let meta = FileMeta::from(&std::fs::metadata(root.join("docs").join("a.txt")).unwrap());
+    let etag = String::from_utf8(static_files::etag(&meta).to_bytearray()).unwrap();
+
+    let content = get(&files, "/docs/a.txt").await.ok().unwrap();
+    assert_eq!(header(&content, "ETag"), Some(etag.as_str()));
+    let modified = header(&content, "Last-Modified").unwrap().to_string();
+    let secs = HttpDate::parse_str(&modified).ok().unwrap().secs();
+    assert_eq!(Some(secs), meta.modified.map(|t| HttpDate::from(t).secs()));
+
+    let request = common::get(&[("If-None-Match", &etag)]);
+    let content = files.serve(&request, b"/docs/a.txt").await.ok().unwrap();
+    assert_eq!(content.captial.status().as_u16(), 304);
+    assert!(body(content).await.is_empty());
+
+    let request = common::get(&[("If-Modified-Since", &modified)]);
+    let content = files.serve(&request, b"/docs/a.txt").await.ok().unwrap();
+    assert_eq!(content.captial.status().as_u16(), 304);
+
+    let request = common::get(&[("If-None-Match", "\"other\"")]);
+    let content = files.serve(&request, b"/docs/a.txt").await.ok().unwrap();
+    assert_eq!(content.captial.status().as_u16(), 200);
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[tokio::test]
+async fn head_has_the_get_headers_without_a_body() {
+    let root = site("head");
+    let files = StaticFiles::new(&root, StaticOptions::default());
+
+    let request = RequestHeader {
+        method: Method::Head,
+        ..Default::default()
+    };
+    let content = files.serve(&request, b"/docs/a.txt").await.ok().unwrap();
+    assert!(header(&content, "ETag").is_some());
+
+    let writer = Arc::new(tokio::sync::RwLock::new(Vec::new()));
+    Response::new(writer.clone(), content).head_only(true).send().await.ok().unwrap();
+    let wire = String::from_utf8(writer.read().await.clone()).unwrap();
+    assert!(wire.contains("\r\nContent-Length: 5\r\n"));
+    assert!(wire.ends_with("\r\n\r\n"));
+
+    let request = RequestHeader {
+        method: Method::Post,
+        ..Default::default()
+    };
+    let content = files.serve(&request, b"/docs/a.txt").await.ok().unwrap();
+    assert_eq!(content.captial.status().as_u16(), 405);
+    assert_eq!(header(&content, "Allow"), Some("GET, HEAD"));
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+/// app.js with .br and .gz siblings, lib.js with only .gz, plain.js alone
+fn precompressed_site(name: &str) -> std::path::PathBuf {
+    let root = scratch_dir(name);
+    std::fs::write(root.join("app.js"), b"identity").unwrap();
+    std::fs::write(root.join("app.js.br"), b"brotli").unwrap();
+    std::fs::write(root.join("app.js.gz"), b"gzip").unwrap();
+    std::fs::write(root.join("lib.js"), b"lib").unwrap();
+    std::fs::write(root.join("lib.js.gz"), b"lib gzip").unwrap();
+    std::fs::write(root.join("plain.js"), b"plain").unwrap();
+    root
+}
+
+/// (Content-Encoding, Vary, body)
+async fn negotiated(files: &StaticFiles, target: &str, accept: Option<&str>) -> (Option<String>, Option<String>, Vec<u8>) {
+    let request = match accept {
+        Some(v) => common::get(&[("Accept-Encoding", v)]),
+        None => common::get(&[])
+    };
+
+    let content = files.serve(&request, target.as_bytes()).await.ok().unwrap();
+    assert_eq!(header(&content, "Content-Type"), Some("text/javascript; charset=utf-8"));
+
+    let coding = header(&content, "Content-Encoding").map(|v| v.to_string());
+    let vary = header(&content, "Vary").map(|v| v.to_string());
+
+    (coding, vary, body(content).await)
+}
+
+#[tokio::test]
+async fn precompressed_siblings_follow_accept_encoding() {
+    let root = precompressed_site("precompressed");
+    let files = StaticFiles::new(&root, StaticOptions::default());
+    let vary = Some("Accept-Encoding".to_string());
+
+    let cases: &[(Option<&str>, Option<&str>, &[u8])] = &[
+        (None, None, b"identity"),
+        (Some("gzip"), Some("gzip"), b"gzip"),
+        (Some("br"), Some("br"), b"brotli"),
+        // equal weights prefer br
+        (Some("gzip, br"), Some("br"), b"brotli"),
+        (Some("br;q=0.5, gzip"), Some("gzip"), b"gzip"),
+        (Some("br;q=0, gzip;q=0"), None, b"identity"),
+        (Some("deflate"), None, b"identity")];
+
+    for (accept, coding, data) in cases {
+        let r = negotiated(&files, "/app.js", *accept).await;
+        assert_eq!(r, (coding.map(|c| c.to_string()), vary.clone(), data.to_vec()), "{:?}", accept);
+    }
+
+    // only the siblings that exist are offered
+    let r = negotiated(&files, "/lib.js", Some("br")).await;
+    assert_eq!(r, (None, vary.clone(), b"lib".to_vec()));
+    let r = negotiated(&files, "/lib.js", Some("br, gzip")).await;
+    assert_eq!(r, (Some("gzip".to_string()), vary.clone(), b"lib gzip".to_vec()));
+
+    // nothing to vary on without siblings
+    let r = negotiated(&files, "/plain.js", Some("br, gzip")).await;
+    assert_eq!(r, (None, None, b"plain".to_vec()));
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[tokio::test]
+async fn precompressed_variants_have_their_own_etag() {
+    let root = precompressed_site("precompressed-etag");
+    let files = StaticFiles::new(&root, StaticOptions::default());
+
+    let identity = files.serve(&common::get(&[]), b"/app.js").await.ok().unwrap();
+    let br = files.serve(&common::get(&[("Accept-Encoding", "br")]), b"/app.js").await.ok().unwrap();
+    assert_ne!(header(&identity, "ETag"), header(&br, "ETag"));
+
+    let files = StaticFiles::new(&root, StaticOptions {
+        precompressed: false,
+        ..Default::default()
+    });
+    let r = negotiated(&files, "/app.js", Some("br, gzip")).await;
+    assert_eq!(r, (None, None, b"identity".to_vec()));
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
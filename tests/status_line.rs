@@ -0,0 +1,43 @@
+use tokio_httpserver::*;
+use tokio_httpserver::response::{ResponseBuilder, StatusCode};
+
+fn status_line(content: &ResponseContent) -> String {
+    let mut buf = Vec::new();
+    content.captial.write(&mut buf);
+    String::from_utf8(buf).unwrap()
+}
+
+#[test]
+fn status_and_reason() {
+    let content = ResponseBuilder::new().status(StatusCode::CREATED).build().ok().unwrap();
+    assert_eq!(content.captial.status(), StatusCode::CREATED);
+    assert_eq!(status_line(&content), "HTTP/1.1 201 Created\r\n");
+
+    let content = ResponseBuilder::new().status(StatusCode::from_u16(599).ok().unwrap()).build().ok().unwrap();
+    assert_eq!(content.captial.status().as_u16(), 599);
+    assert_eq!(status_line(&content), "HTTP/1.1 599 \r\n");
+
+    let content = ResponseBuilder::new().status(StatusCode::NOT_FOUND)
+        .reason("Gone\tFishing caf\u{e9}").build().ok().unwrap();
+    assert_eq!(status_line(&content), "HTTP/1.1 404 Gone\tFishing caf\u{e9}\r\n");
+
+    assert!(StatusCode::from_u16(99).is_err());
+    assert!(StatusCode::from_u16(1000).is_err());
+}
+
+#[test]
+fn reason_cannot_split_the_response() {
+    for reason in ["OK\r\nSet-Cookie: a=1", "OK\n", "OK\r", "O\0K", "O\x7fK"].iter() {
+        match ResponseBuilder::new().reason(reason).build() {
+            Err(e) => assert_eq!(e.status(), 500),
+            Ok(_) => panic!("{:?} accepted", reason)
+        }
+
+        let mut captial = ResponseCaptial::default();
+        assert!(captial.set_reason(reason).is_err());
+
+        let mut buf = Vec::new();
+        captial.write(&mut buf);
+        assert_eq!(buf, b"HTTP/1.1 200 OK\r\n");
+    }
+}
@@ -0,0 +1,62 @@
+use std::collections::HashMap;
+
+use tokio_httpserver::*;
+use tokio_httpserver::header::{ContentType, TypedHeader};
+use tokio_httpserver::body::{structured, Format};
+
+fn content_type(value: &str) -> ContentType {
+    ContentType::parse(value.as_bytes()).ok().unwrap()
+}
+
+#[test]
+fn format_from_content_type() {
+    let cases = [
+        ("application/json", Some(Format::Json)),
+        ("application/vnd.api+json", Some(Format::Json)),
+        ("application/msgpack", Some(Format::MessagePack)),
+        ("application/x-msgpack", Some(Format::MessagePack)),
+        ("application/vnd.msgpack", Some(Format::MessagePack)),
+        ("application/cbor", Some(Format::Cbor)),
+        ("application/senml+cbor", Some(Format::Cbor)),
+        ("text/plain", None)];
+
+    for (ct, format) in cases.iter() {
+        assert_eq!(Format::from_content_type(&content_type(ct)), *format, "{}", ct);
+    }
+
+    for format in [Format::Json, Format::MessagePack, Format::Cbor].iter() {
+        assert_eq!(Format::from_content_type(&format.content_type()), Some(*format));
+    }
+}
+
+#[test]
+fn round_trip() {
+    let mut value = HashMap::new();
+    value.insert("name".to_string(), vec![1u32, 2, 3]);
+    value.insert("empty".to_string(), Vec::new());
+
+    for format in [Format::Json, Format::MessagePack, Format::Cbor].iter() {
+        let bytes = structured::encode(*format, &value).ok().unwrap();
+        let decoded = structured::decoder::<HashMap<String, Vec<u32>>>(*format, &bytes).ok().unwrap();
+        let body = Body::Structured(*format, decoded);
+
+        assert_eq!(body.structured_ref::<HashMap<String, Vec<u32>>>(), Some(&value), "{:?}", format);
+    }
+}
+
+#[test]
+fn invalid_bodies_are_400() {
+    // a map header promising more entries than follow
+    for format in [Format::MessagePack, Format::Cbor].iter() {
+        match structured::decoder::<HashMap<String, u32>>(*format, b"\x85\xa1a") {
+            Err(e) => assert_eq!(e.status(), 400, "{:?}", format),
+            Ok(_) => panic!("truncated {:?} accepted", format)
+        }
+    }
+
+    let bytes = structured::encode(Format::Cbor, &"text").ok().unwrap();
+    match structured::decoder::<Vec<u32>>(Format::Cbor, &bytes) {
+        Err(e) => assert_eq!(e.status(), 400),
+        Ok(_) => panic!("cbor string accepted as an array")
+    }
+}
@@ -0,0 +1,19 @@
+use tokio_httpserver::body::CharsetMode;
+use tokio_httpserver::body::urlencoded;
+
+#[test]
+fn invalid_bytes_follow_charset_mode() {
+    let body = b"name=caf%E9&ok=1";
+
+    match urlencoded::parse(body, Some("utf-8"), CharsetMode::Strict) {
+        Err(e) => assert_eq!(e.status(), 400),
+        Ok(_) => panic!("invalid utf-8 accepted in strict mode")
+    }
+
+    let form = urlencoded::parse(body, Some("utf-8"), CharsetMode::Lossy).ok().unwrap();
+    assert_eq!(form.get("name"), Some("caf\u{fffd}"));
+    assert_eq!(form.get("ok"), Some("1"));
+
+    let form = urlencoded::parse(body, Some("iso-8859-1"), CharsetMode::Strict).ok().unwrap();
+    assert_eq!(form.get("name"), Some("café"));
+}